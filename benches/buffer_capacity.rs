@@ -0,0 +1,49 @@
+//! Measures how `ReaderOptions::buffer_capacity` affects reading a large file with long rows.
+//!
+//! Run with `cargo bench --bench buffer_capacity`.
+
+use csv_pipeline::{Pipeline, ReaderOptions};
+use std::time::{Duration, Instant};
+
+const ROWS: usize = 20_000;
+const ROW_LEN: usize = 2_000;
+const RUNS: u32 = 5;
+
+fn read(path: &std::path::Path, capacity: Option<usize>) -> Duration {
+	let mut options = ReaderOptions::new();
+	if let Some(capacity) = capacity {
+		options = options.buffer_capacity(capacity);
+	}
+	let start = Instant::now();
+	let count = Pipeline::from_path_with(path, options)
+		.unwrap()
+		.build()
+		.inspect(|row| assert!(row.is_ok()))
+		.count();
+	let elapsed = start.elapsed();
+	assert_eq!(count, ROWS);
+	elapsed
+}
+
+fn main() {
+	let path = std::env::temp_dir().join("csv-pipeline-bench-buffer-capacity.csv");
+	let mut source = String::from("A,B,C\n");
+	for i in 0..ROWS {
+		let text = "x".repeat(ROW_LEN);
+		source += &format!("{},{},\"{}\"\n", i, text, text);
+	}
+	std::fs::write(&path, &source).unwrap();
+	println!("{} rows, {} MB", ROWS, source.len() / 1_000_000);
+
+	let capacities = [None, Some(1 << 10), Some(1 << 16), Some(1 << 20)];
+	for capacity in capacities {
+		read(&path, capacity);
+		let total: Duration = (0..RUNS).map(|_| read(&path, capacity)).sum();
+		let label = match capacity {
+			Some(capacity) => format!("{} bytes", capacity),
+			None => "default".to_string(),
+		};
+		println!("{:>15}: {:?} per run", label, total / RUNS);
+	}
+	std::fs::remove_file(&path).unwrap();
+}
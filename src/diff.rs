@@ -0,0 +1,48 @@
+use crate::{Headers, Row};
+
+/// The differences between two pipelines, which you can get using [`Pipeline::diff`](crate::Pipeline::diff).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffReport {
+	/// Rows whose key only appears in the new pipeline
+	pub added: Vec<Row>,
+	/// Rows whose key only appears in the old pipeline
+	pub removed: Vec<Row>,
+	/// Rows whose key appears in both pipelines, but with different fields
+	pub changed: Vec<ChangedRow>,
+}
+impl DiffReport {
+	/// Returns true if the pipelines have the same rows
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+	}
+}
+
+/// A row that has changed between two pipelines
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedRow {
+	pub old: Row,
+	pub new: Row,
+	/// Names of the columns whose fields are different
+	pub columns: Vec<String>,
+}
+
+/// Get the names of the columns whose fields are different. Columns that only exist on one side count as different.
+pub(crate) fn changed_columns(
+	old_headers: &Headers,
+	old: &Row,
+	new_headers: &Headers,
+	new: &Row,
+) -> Vec<String> {
+	let mut columns = Vec::new();
+	for name in new_headers {
+		if old_headers.get_field(old, name) != new_headers.get_field(new, name) {
+			columns.push(name.to_string());
+		}
+	}
+	for name in old_headers {
+		if !new_headers.contains(name) {
+			columns.push(name.to_string());
+		}
+	}
+	columns
+}
@@ -1,11 +1,18 @@
-use crate::Row;
+use crate::{Error, Row};
 use csv::StringRecordIter;
 use std::collections::BTreeMap;
 use std::fmt;
+use std::sync::Arc;
 
-/// The headers of a CSV file
+/// The headers of a CSV file.
+///
+/// Cloning is cheap, since clones share their data until one of them is modified.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Headers {
+	inner: Arc<HeadersInner>,
+}
+#[derive(Debug, Clone, PartialEq)]
+struct HeadersInner {
 	indexes: BTreeMap<String, usize>,
 	row: Row,
 }
@@ -25,8 +32,10 @@ impl fmt::Display for RenameError {
 impl Headers {
 	pub fn new() -> Self {
 		Headers {
-			indexes: BTreeMap::new(),
-			row: Row::new(),
+			inner: Arc::new(HeadersInner {
+				indexes: BTreeMap::new(),
+				row: Row::new(),
+			}),
 		}
 	}
 
@@ -35,43 +44,70 @@ impl Headers {
 		if let Some(index) = self.get_index(to) {
 			return Err(RenameError::DuplicateColumn(index));
 		}
-		let index = match self.indexes.remove(from) {
-			Some(index) => index,
-			None => return Err(RenameError::MissingColumn),
-		};
-		self.indexes.insert(to.to_string(), index);
-		let mut row_vec: Vec<_> = self.row.into_iter().collect();
+		if !self.contains(from) {
+			return Err(RenameError::MissingColumn);
+		}
+		let inner = Arc::make_mut(&mut self.inner);
+		let index = inner.indexes.remove(from).unwrap();
+		inner.indexes.insert(to.to_string(), index);
+		let mut row_vec: Vec<_> = inner.row.into_iter().collect();
 		row_vec[index] = to;
-		self.row = row_vec.into_iter().collect();
+		inner.row = row_vec.into_iter().collect();
 		Ok(())
 	}
 
 	/// Returns false if the field already exists
 	pub fn push_field(&mut self, name: &str) -> bool {
-		if self.indexes.contains_key(name) {
+		if self.contains(name) {
 			return false;
 		}
 
-		self.row.push_field(name);
-		self.indexes.insert(name.to_string(), self.row.len() - 1);
+		let inner = Arc::make_mut(&mut self.inner);
+		inner.row.push_field(name);
+		inner.indexes.insert(name.to_string(), inner.row.len() - 1);
 
 		true
 	}
 
 	pub fn contains(&self, name: &str) -> bool {
-		self.indexes.contains_key(name)
+		self.inner.indexes.contains_key(name)
 	}
 
 	pub fn get_field<'a>(&self, row: &'a Row, name: &str) -> Option<&'a str> {
-		self.indexes.get(name).and_then(|index| row.get(*index))
+		self.inner
+			.indexes
+			.get(name)
+			.and_then(|index| row.get(*index))
+	}
+
+	/// Like [`get_field`](Headers::get_field), but returns a [`MissingColumn`](Error::MissingColumn) error if the column doesn't exist, and a [`RowTooShort`](Error::RowTooShort) error if the column exists but the row doesn't have a field for it.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Error, Headers, Row};
+	///
+	/// let headers = Headers::from_row(Row::from(vec!["A", "B"])).unwrap();
+	/// let row = Row::from(vec!["1"]);
+	///
+	/// assert_eq!(headers.get_field_checked(&row, "A").unwrap(), "1");
+	/// assert!(matches!(headers.get_field_checked(&row, "B"), Err(Error::RowTooShort(_, 1))));
+	/// assert!(matches!(headers.get_field_checked(&row, "C"), Err(Error::MissingColumn(_))));
+	/// ```
+	pub fn get_field_checked<'a>(&self, row: &'a Row, name: &str) -> Result<&'a str, Error> {
+		let index = self
+			.get_index(name)
+			.ok_or_else(|| Error::MissingColumn(name.to_string()))?;
+		row.get(index)
+			.ok_or_else(|| Error::RowTooShort(name.to_string(), row.len()))
 	}
 
 	pub fn get_index(&self, name: &str) -> Option<usize> {
-		self.indexes.get(name).copied()
+		self.inner.indexes.get(name).copied()
 	}
 
 	pub fn get_row(&self) -> &Row {
-		&self.row
+		&self.inner.row
 	}
 
 	/// If a column is duplicated, errors with the column name
@@ -87,16 +123,35 @@ impl Headers {
 	}
 }
 
+impl Default for Headers {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl<'a> IntoIterator for &'a Headers {
 	type Item = &'a str;
 	type IntoIter = StringRecordIter<'a>;
 
 	fn into_iter(self) -> StringRecordIter<'a> {
-		self.row.into_iter()
+		self.inner.row.into_iter()
 	}
 }
 impl From<Headers> for Row {
 	fn from(headers: Headers) -> Row {
-		headers.row
+		match Arc::try_unwrap(headers.inner) {
+			Ok(inner) => inner.row,
+			Err(inner) => inner.row.clone(),
+		}
 	}
 }
+
+#[test]
+fn test_clone_on_write() {
+	let mut a = Headers::from_row(Row::from(vec!["A"])).unwrap();
+	let b = a.clone();
+	assert!(Arc::ptr_eq(&a.inner, &b.inner));
+	a.push_field("B");
+	assert_eq!(a.get_row(), &Row::from(vec!["A", "B"]));
+	assert_eq!(b.get_row(), &Row::from(vec!["A"]));
+}
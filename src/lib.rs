@@ -78,15 +78,27 @@
 //! ```
 //!
 
+use std::fmt;
 use std::path::PathBuf;
 
+mod diff;
 mod headers;
+mod multi_delimiter;
+mod options;
 mod pipeline;
 mod pipeline_iterators;
+mod size_limit;
+mod sort;
+mod spill;
+mod summary;
 mod transform;
 
+pub use diff::{ChangedRow, DiffReport};
 pub use headers::Headers;
-pub use pipeline::{Pipeline, PipelineIter};
+pub use options::{NumberFormat, ReaderOptions, TransformOptions};
+pub use pipeline::{CsvReader, Pipeline, PipelineIter, Predicate, SendPipelineIter};
+pub use sort::{Order, SortKey};
+pub use summary::{ColumnSummary, NumericSummary, Summary};
 pub use transform::{Transform, Transformer};
 
 pub mod target;
@@ -102,9 +114,21 @@ impl Target {
 	pub fn stderr() -> target::StderrTarget {
 		target::StderrTarget::new()
 	}
-	pub fn string<'a>(s: &'a mut String) -> target::StringTarget {
+	pub fn string(s: &mut String) -> target::StringTarget<'_> {
 		target::StringTarget::new(s)
 	}
+	pub fn fixed_width<W: std::io::Write>(writer: W) -> target::FixedWidthTarget<W> {
+		target::FixedWidthTarget::new(writer)
+	}
+	pub fn markdown<W: std::io::Write>(writer: W) -> target::MarkdownTarget<W> {
+		target::MarkdownTarget::new(writer)
+	}
+	pub fn html<W: std::io::Write>(writer: W) -> target::HtmlTarget<W> {
+		target::HtmlTarget::new(writer)
+	}
+	pub fn table<W: std::io::Write>(writer: W) -> target::TableTarget<W> {
+		target::TableTarget::new(writer)
+	}
 }
 
 /// Alias of [`csv::StringRecord`]
@@ -117,12 +141,16 @@ pub type RowResult = Result<Row, PlError>;
 pub struct PlError {
 	pub error: Error,
 	pub source: usize,
+	/// The row that caused the error, if the error was caused by a specific row. Steps that take ownership of the row, like [`Pipeline::map`], don't include it.
+	pub row: Option<Row>,
 }
 
 #[derive(Debug)]
 pub enum Error {
-	/// CSV and IO errors are in here.
+	/// CSV errors, and IO errors that happen while reading or writing CSV data.
 	Csv(csv::Error),
+	/// A file couldn't be opened, or its extension isn't supported by [`Pipeline::from_path_with`] (returned as [`InvalidInput`](std::io::ErrorKind::InvalidInput)). The message includes the path.
+	Io(std::io::Error),
 	/// The column of this name is missing.
 	MissingColumn(String),
 	/// This column name appears twice.
@@ -131,12 +159,83 @@ pub enum Error {
 	InvalidField(String),
 	/// Two pipeline sources don't have the same headers.
 	MismatchedHeaders(Row, Row),
+	/// The rows were grouped into more groups than the specified maximum.
+	TooManyGroups(usize),
+	/// This transform doesn't support merging, which is required for spilling groups to disk.
+	NotMergeable(String),
+	/// This key appears in more than one row.
+	DuplicateKey(String),
+	/// The value of this column overflowed.
+	Overflow(String),
+	/// Two rows have the same key but different fields. Contains the first row with the key, followed by the conflicting row.
+	ConflictingRows(Row, Row),
+	/// A field is longer than the maximum number of bytes set by [`ReaderOptions::max_field_size`].
+	FieldTooLarge(usize),
+	/// A row is longer than the maximum number of bytes set by [`ReaderOptions::max_row_size`].
+	RowTooLarge(usize),
+	/// The row is too short to have a field for this column, which usually means the data is ragged. Contains the column name and the number of fields in the row.
+	RowTooShort(String, usize),
+	/// This environment variable isn't set, or isn't valid unicode.
+	MissingEnvVar(String),
+	/// The closure of [`Pipeline::add_col_ctx`] failed. Contains the column name, the index of the row in the step's input, and the error.
+	AddCol(String, usize, Box<Error>),
 }
 impl Error {
 	pub fn at_source(self, source: usize) -> PlError {
 		PlError {
 			error: self,
 			source,
+			row: None,
+		}
+	}
+	/// Like [`at_source`](Error::at_source), but also keeps the row that caused the error
+	pub fn at_row(self, source: usize, row: &Row) -> PlError {
+		PlError {
+			error: self,
+			source,
+			row: Some(row.clone()),
 		}
 	}
 }
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let join = |row: &Row| row.iter().collect::<Vec<_>>().join(",");
+		match self {
+			Error::Csv(e) => write!(f, "{}", e),
+			Error::Io(e) => write!(f, "{}", e),
+			Error::MissingColumn(col) => write!(f, "Missing column {}", col),
+			Error::DuplicateColumn(col) => write!(f, "Duplicate column {}", col),
+			Error::InvalidField(field) => write!(f, "Invalid field {}", field),
+			Error::MismatchedHeaders(a, b) => {
+				write!(f, "Mismatched headers {} and {}", join(a), join(b))
+			}
+			Error::TooManyGroups(max) => write!(f, "More than {} groups", max),
+			Error::NotMergeable(name) => write!(f, "Transform {} can't be merged", name),
+			Error::DuplicateKey(key) => write!(f, "Duplicate key {}", key),
+			Error::Overflow(col) => write!(f, "Overflow in column {}", col),
+			Error::ConflictingRows(a, b) => {
+				write!(f, "Conflicting rows {} and {}", join(a), join(b))
+			}
+			Error::FieldTooLarge(max) => write!(f, "Field is larger than {} bytes", max),
+			Error::RowTooLarge(max) => write!(f, "Row is larger than {} bytes", max),
+			Error::RowTooShort(col, len) => {
+				write!(
+					f,
+					"Row has {} fields, which is too few for column {}",
+					len, col
+				)
+			}
+			Error::MissingEnvVar(var) => write!(f, "Environment variable {} is not set", var),
+			Error::AddCol(col, index, e) => {
+				write!(f, "Could not add column {} to row {}: {}", col, index, e)
+			}
+		}
+	}
+}
+impl std::error::Error for Error {}
+impl fmt::Display for PlError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{} in source {}", self.error, self.source)
+	}
+}
+impl std::error::Error for PlError {}
@@ -0,0 +1,109 @@
+use std::io::{self, BufRead};
+
+/// Converts lines separated by a multi-character delimiter into comma-separated CSV, so it can be parsed by a [`csv::Reader`]. Quotes have no special meaning in the input, so fields can't contain the delimiter or newlines.
+pub(crate) struct MultiDelimiterReader<R> {
+	inner: io::BufReader<R>,
+	delimiter: Vec<u8>,
+	line: Vec<u8>,
+	/// The converted line
+	output: Vec<u8>,
+	/// How much of `output` has been read
+	position: usize,
+}
+impl<R: io::Read> MultiDelimiterReader<R> {
+	pub fn new(inner: R, delimiter: &str) -> Self {
+		Self {
+			inner: io::BufReader::new(inner),
+			delimiter: delimiter.as_bytes().to_vec(),
+			line: Vec::new(),
+			output: Vec::new(),
+			position: 0,
+		}
+	}
+
+	/// Read and convert the next line. Returns false at the end of the input.
+	fn convert_line(&mut self) -> io::Result<bool> {
+		self.line.clear();
+		self.output.clear();
+		self.position = 0;
+		if self.inner.read_until(b'\n', &mut self.line)? == 0 {
+			return Ok(false);
+		}
+		let mut line = &self.line[..];
+		for suffix in [&b"\n"[..], b"\r"] {
+			line = line.strip_suffix(suffix).unwrap_or(line);
+		}
+		for (i, field) in split(line, &self.delimiter).enumerate() {
+			if i > 0 {
+				self.output.push(b',');
+			}
+			write_field(&mut self.output, field);
+		}
+		self.output.push(b'\n');
+		Ok(true)
+	}
+}
+impl<R: io::Read> io::Read for MultiDelimiterReader<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		while self.position >= self.output.len() {
+			if !self.convert_line()? {
+				return Ok(0);
+			}
+		}
+		let len = buf.len().min(self.output.len() - self.position);
+		buf[..len].copy_from_slice(&self.output[self.position..self.position + len]);
+		self.position += len;
+		Ok(len)
+	}
+}
+
+fn split<'a>(mut line: &'a [u8], delimiter: &'a [u8]) -> impl Iterator<Item = &'a [u8]> {
+	let mut done = false;
+	std::iter::from_fn(move || {
+		if done {
+			return None;
+		}
+		match line.windows(delimiter.len()).position(|w| w == delimiter) {
+			Some(i) => {
+				let field = &line[..i];
+				line = &line[i + delimiter.len()..];
+				Some(field)
+			}
+			None => {
+				done = true;
+				Some(line)
+			}
+		}
+	})
+}
+
+/// Write a field as CSV, quoting it if needed
+fn write_field(output: &mut Vec<u8>, field: &[u8]) {
+	if !field
+		.iter()
+		.any(|b| matches!(b, b',' | b'"' | b'\n' | b'\r'))
+	{
+		output.extend_from_slice(field);
+		return;
+	}
+	output.push(b'"');
+	for byte in field {
+		if *byte == b'"' {
+			output.push(b'"');
+		}
+		output.push(*byte);
+	}
+	output.push(b'"');
+}
+
+#[test]
+fn test_multi_delimiter() {
+	use std::io::Read;
+
+	let source = "A||B||C\r\n1||x,y||say \"hi\"\n||2||\n";
+	let mut csv = String::new();
+	MultiDelimiterReader::new(source.as_bytes(), "||")
+		.read_to_string(&mut csv)
+		.unwrap();
+	assert_eq!(csv, "A,B,C\n1,\"x,y\",\"say \"\"hi\"\"\"\n,2,\n");
+}
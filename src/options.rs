@@ -0,0 +1,206 @@
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// Options for reading a CSV file, used with [`Pipeline::from_path_with`](crate::Pipeline::from_path_with) and [`Pipeline::from_reader_with`](crate::Pipeline::from_reader_with).
+#[derive(Debug, Clone, Default)]
+pub struct ReaderOptions {
+	pub(crate) null_token: Option<String>,
+	pub(crate) skip_empty_rows: bool,
+	pub(crate) buffer_capacity: Option<usize>,
+	pub(crate) delimiter: Option<String>,
+	pub(crate) max_field_size: Option<usize>,
+	pub(crate) max_row_size: Option<usize>,
+}
+impl ReaderOptions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Read fields matching this token as empty fields, for example `\N` for Postgres `COPY` output.
+	pub fn null_token(mut self, token: &str) -> Self {
+		self.null_token = Some(token.to_string());
+		self
+	}
+
+	/// Skip rows where every field is empty, like the `,,` rows some spreadsheet exports end with. Blank lines are always skipped.
+	pub fn skip_empty_rows(mut self) -> Self {
+		self.skip_empty_rows = true;
+		self
+	}
+
+	/// Set the capacity of the read buffer in bytes, which can speed up reading files with very long rows. Only used when reading from a path.
+	pub fn buffer_capacity(mut self, capacity: usize) -> Self {
+		self.buffer_capacity = Some(capacity);
+		self
+	}
+
+	/// Use this delimiter instead of the one implied by the file extension. Only used when reading from a path.
+	///
+	/// Delimiters longer than one byte, like `||`, are supported in a limited way: quotes have no special meaning, so fields can't contain the delimiter or newlines. Panics if the delimiter is empty.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, ReaderOptions};
+	///
+	/// let csv = Pipeline::from_path_with("test/DoublePipe.txt", ReaderOptions::new().delimiter("||"))
+	///   .unwrap()
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "A,B\n1,\"x,y\"\n");
+	/// ```
+	pub fn delimiter(mut self, delimiter: &str) -> Self {
+		assert!(!delimiter.is_empty(), "Delimiter can't be empty");
+		self.delimiter = Some(delimiter.to_string());
+		self
+	}
+
+	/// Return a [`FieldTooLarge`](crate::Error::FieldTooLarge) error for fields that are longer than this many bytes.
+	pub fn max_field_size(mut self, bytes: usize) -> Self {
+		self.max_field_size = Some(bytes);
+		self
+	}
+
+	/// Return a [`RowTooLarge`](crate::Error::RowTooLarge) error for rows that are longer than this many bytes in the input, including delimiters, quotes and the line ending. This protects against running out of memory on pathological input, since reading stops at the first row that is too large. When reading from a path, rows are checked while they are read, so a huge row is never read fully into memory. Otherwise they are checked after being read. With a multi-character [`delimiter`](Self::delimiter), each line is read fully and converted to comma-separated CSV before it's checked, so the size is measured on the converted row: delimiters count as one byte, fields may gain quotes, and the line ending counts as one byte.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Error, Pipeline, ReaderOptions};
+	///
+	/// let reader = csv::Reader::from_reader("A,B\n1,2\n100000,2\n".as_bytes());
+	/// let err = Pipeline::from_reader_with(reader, ReaderOptions::new().max_row_size(6))
+	///   .unwrap()
+	///   .collect_into_string()
+	///   .unwrap_err();
+	///
+	/// assert!(matches!(err.error, Error::RowTooLarge(6)));
+	/// ```
+	pub fn max_row_size(mut self, bytes: usize) -> Self {
+		self.max_row_size = Some(bytes);
+		self
+	}
+}
+
+/// Options for grouping rows, used with [`Pipeline::transform_into_with`](crate::Pipeline::transform_into_with).
+#[derive(Debug, Clone, Default)]
+pub struct TransformOptions {
+	pub(crate) max_groups: Option<usize>,
+	/// `None` to use `DefaultHasher`
+	pub(crate) hasher: Option<RowHasher>,
+	pub(crate) spill: Option<(PathBuf, usize)>,
+}
+impl TransformOptions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Use a different hasher to group rows, for example a faster one, a seeded one, or one that gives the same hashes on every platform. A hasher is built for each row using [`BuildHasher::build_hasher`]. Rows are only grouped together if their key fields are equal, so hash collisions don't merge groups.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, TransformOptions, Transformer};
+	/// use std::collections::hash_map::DefaultHasher;
+	/// use std::hash::{BuildHasher, Hasher};
+	///
+	/// struct Seeded(u64);
+	/// impl BuildHasher for Seeded {
+	///   type Hasher = DefaultHasher;
+	///   fn build_hasher(&self) -> DefaultHasher {
+	///     let mut hasher = DefaultHasher::new();
+	///     hasher.write_u64(self.0);
+	///     hasher
+	///   }
+	/// }
+	///
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .transform_into_with(
+	///     || vec![Transformer::new("Country").keep_unique()],
+	///     TransformOptions::new().hasher(Seeded(42)),
+	///   )
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "Country\nNorway\nTuvalu\n");
+	/// ```
+	pub fn hasher<B>(mut self, build_hasher: B) -> Self
+	where
+		B: BuildHasher + 'static,
+		B::Hasher: 'static,
+	{
+		self.hasher = Some(RowHasher(Rc::new(move || {
+			Box::new(build_hasher.build_hasher())
+		})));
+		self
+	}
+
+	/// Return a [`TooManyGroups`](crate::Error::TooManyGroups) error if the rows would be grouped into more than this many groups, instead of running out of memory.
+	pub fn max_groups(mut self, max_groups: usize) -> Self {
+		self.max_groups = Some(max_groups);
+		self
+	}
+
+	/// When there are more than `max_groups_in_memory` groups in memory, write them to files in a new directory inside `dir`. After all rows have been read, the files are merged back one at a time, and the directory is removed. This lets you group data that doesn't fit in memory.
+	///
+	/// Only mergeable transforms can be spilled, which includes [`keep_unique`](crate::Transformer::keep_unique), [`sum`](crate::Transformer::sum), [`sum_lenient`](crate::Transformer::sum_lenient), [`sum_checked`](crate::Transformer::sum_checked), [`count`](crate::Transformer::count), [`count_invalid`](crate::Transformer::count_invalid), [`min_text`](crate::Transformer::min_text), [`max_text`](crate::Transformer::max_text), [`min_date`](crate::Transformer::min_date), [`max_date`](crate::Transformer::max_date), [`collect_json`](crate::Transformer::collect_json) and [`weighted_mean`](crate::Transformer::weighted_mean). Other transforms result in a [`NotMergeable`](crate::Error::NotMergeable) error. When groups have been spilled, the order of the output rows is not preserved, and [`max_groups`](TransformOptions::max_groups) only limits the groups in memory.
+	pub fn spill_to_disk<P: Into<PathBuf>>(mut self, dir: P, max_groups_in_memory: usize) -> Self {
+		self.spill = Some((dir.into(), max_groups_in_memory));
+		self
+	}
+}
+
+/// Builds a hasher for each row, from a [`BuildHasher`] set using [`TransformOptions::hasher`]
+#[derive(Clone)]
+pub(crate) struct RowHasher(pub Rc<dyn Fn() -> Box<dyn Hasher>>);
+impl fmt::Debug for RowHasher {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "RowHasher")
+	}
+}
+
+/// How to write numbers computed by [`Pipeline::map_col_numeric`](crate::Pipeline::map_col_numeric).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberFormat {
+	/// As short as possible, so `1.0` becomes `1`.
+	Shortest,
+	/// With this many decimals.
+	Decimals(usize),
+	/// With the same number of integer digits and decimals as the original field, so `007` stays zero-padded and `1.50` keeps its trailing zero. Fields in exponent notation are written as short as possible.
+	Preserve,
+}
+impl NumberFormat {
+	pub(crate) fn format(&self, original: &str, value: f64) -> String {
+		match self {
+			NumberFormat::Shortest => value.to_string(),
+			NumberFormat::Decimals(decimals) => format!("{:.*}", decimals, value),
+			NumberFormat::Preserve => {
+				let unsigned = original.trim_start_matches(['-', '+']);
+				if unsigned.contains(['e', 'E']) {
+					return value.to_string();
+				}
+				let (int_part, decimals) = match unsigned.split_once('.') {
+					Some((int_part, fraction)) => (int_part, fraction.len()),
+					None => (unsigned, 0),
+				};
+				let mut width = int_part.len();
+				if decimals > 0 {
+					width += decimals + 1;
+				}
+				if value.is_sign_negative() {
+					width += 1;
+				}
+				format!(
+					"{:0width$.decimals$}",
+					value,
+					width = width,
+					decimals = decimals
+				)
+			}
+		}
+	}
+}
@@ -1,28 +1,88 @@
-use super::headers::Headers;
+use super::headers::{Headers, RenameError};
+use crate::diff::{changed_columns, ChangedRow, DiffReport};
+use crate::multi_delimiter::MultiDelimiterReader;
+use crate::options::{NumberFormat, ReaderOptions, TransformOptions};
 use crate::pipeline_iterators::{
-	AddCol, Filter, FilterCol, Flush, MapCol, MapRow, PipelinesChain, Select, TransformInto,
-	Validate, ValidateCol,
+	parse_kv, row_key, AddCol, Aggregate, Align, Branch, BranchInput, BranchShared, Buffered,
+	DedupConflicts, ExpandKv, FillGaps, Filter, FilterCol, FlatMap, Flush, GapFill, KeyFilter, Lag,
+	Lead, MapCol, MapCols, MapRow, MovingAverage, PartitionBy, PipelinesChain, RunningExtreme,
+	Select, SelectIndices, Stateful, TransformInto, TransformSorted, UpdateWhere, Validate,
+	ValidateCol,
 };
+use crate::size_limit::{is_row_size_exceeded, RowSizeLimit};
+use crate::sort::{rank, Order, SortKey, SortValue};
+use crate::spill::Spill;
+use crate::summary::Summary;
 use crate::target::{StringTarget, Target};
 use crate::transform::Transform;
 use crate::{Error, PlError, Row, RowResult};
 use csv::{Reader, ReaderBuilder, StringRecordsIntoIter};
 use linked_hash_map::LinkedHashMap;
 use std::borrow::BorrowMut;
-use std::io;
-use std::path::Path;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+
+/// A boxed closure that decides whether a row matches, used by [`Pipeline::case_col`]
+pub type Predicate<'a> = Box<dyn FnMut(&Headers, &Row) -> bool + 'a>;
 
 /// The main thing
 pub struct Pipeline<'a> {
 	pub headers: Headers,
 	pub(crate) source: usize,
+	/// The file the pipeline reads from, if it was created from a path
+	pub(crate) file: Option<FileSource>,
+	/// Label of the source the last row was read from, if the pipeline was merged from multiple sources. Steps that read rows ahead of the ones they return set this to `None`, since it no longer matches the returned row.
+	pub(crate) source_label: Option<Rc<RefCell<String>>>,
 	iterator: Box<dyn Iterator<Item = RowResult> + 'a>,
 }
 
 impl<'a> Pipeline<'a> {
-	pub fn from_reader<R: io::Read + 'a>(mut reader: Reader<R>) -> Result<Self, PlError> {
-		let headers_row = reader.headers().unwrap().clone();
-		let row_iterator = RowIter::from_records(0, reader.into_records());
+	pub fn from_reader<R: io::Read + 'a>(reader: Reader<R>) -> Result<Self, PlError> {
+		Self::from_reader_options(reader, &ReaderOptions::new())
+	}
+
+	/// Create a pipeline from a CSV reader, using the specified [`ReaderOptions`].
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, ReaderOptions};
+	///
+	/// let reader = csv::Reader::from_reader("A,B\n1,2\n,\n".as_bytes());
+	/// let csv = Pipeline::from_reader_with(reader, ReaderOptions::new().skip_empty_rows())
+	///   .unwrap()
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "A,B\n1,2\n");
+	/// ```
+	pub fn from_reader_with<R: io::Read + 'a>(
+		reader: Reader<R>,
+		options: ReaderOptions,
+	) -> Result<Self, PlError> {
+		Self::from_reader_options(reader, &options)
+	}
+
+	fn from_reader_options<R: io::Read + 'a>(
+		mut reader: Reader<R>,
+		options: &ReaderOptions,
+	) -> Result<Self, PlError> {
+		let headers_row = match reader.headers() {
+			Ok(headers_row) => headers_row.clone(),
+			Err(e) => return Err(RowIter::<R>::csv_error(e, options.max_row_size).at_source(0)),
+		};
+		let row_iterator = RowIter::from_records(0, reader.into_records())
+			.null_token(options.null_token.clone())
+			.skip_empty_rows(options.skip_empty_rows)
+			.max_sizes(options.max_field_size, options.max_row_size);
 		Ok(Pipeline {
 			headers: match Headers::from_row(headers_row) {
 				Ok(headers) => headers,
@@ -31,25 +91,211 @@ impl<'a> Pipeline<'a> {
 				}
 			},
 			source: 0,
+			file: None,
+			source_label: None,
 			iterator: Box::new(row_iterator),
 		})
 	}
 
+	/// Create a pipeline from CSV data that has already been read into memory. This is useful in async code, where you can read the file asynchronously and hand over the bytes.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_bytes(b"A,B\n1,2\n")
+	///   .unwrap()
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "A,B\n1,2\n");
+	/// ```
+	pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, PlError> {
+		Self::from_reader(Reader::from_reader(bytes))
+	}
+
+	/// Create a pipeline from a string of CSV data.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_str("A,B\n1,2\n")
+	///   .unwrap()
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "A,B\n1,2\n");
+	/// ```
+	#[allow(clippy::should_implement_trait)]
+	pub fn from_str(csv: &'a str) -> Result<Self, PlError> {
+		Self::from_bytes(csv.as_bytes())
+	}
+
+	/// Same as [`Pipeline::from_str`], but takes ownership of the string.
+	pub fn from_string(csv: String) -> Result<Self, PlError> {
+		Self::from_reader(Reader::from_reader(io::Cursor::new(csv)))
+	}
+
 	/// Create a pipeline from a CSV or TSV file.
 	pub fn from_path<P: AsRef<Path>>(file_path: P) -> Result<Self, PlError> {
+		Self::from_path_with(file_path, ReaderOptions::new())
+	}
+
+	/// Create a pipeline from a CSV or TSV file, using the specified [`ReaderOptions`].
+	///
+	/// Other extensions are only accepted if [`ReaderOptions::delimiter`] is set. Otherwise, an [`Io`](Error::Io) error of kind [`InvalidInput`](io::ErrorKind::InvalidInput) is returned.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, ReaderOptions};
+	///
+	/// let csv = Pipeline::from_path_with("test/AB.csv", ReaderOptions::new().null_token("2"))
+	///   .unwrap()
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "A,B\n1,\n");
+	/// ```
+	pub fn from_path_with<P: AsRef<Path>>(
+		file_path: P,
+		options: ReaderOptions,
+	) -> Result<Self, PlError> {
 		let ext = file_path.as_ref().extension().unwrap_or_default();
 		let delimiter = match ext.to_string_lossy().as_ref() {
 			"tsv" => b'\t',
 			"csv" => b',',
-			_ => panic!("Unsupported file {}", file_path.as_ref().display()),
+			_ if options.delimiter.is_some() => b',',
+			_ => {
+				let msg = format!("Unsupported file {}", file_path.as_ref().display());
+				let error = io::Error::new(io::ErrorKind::InvalidInput, msg);
+				return Err(Error::Io(error).at_source(0));
+			}
+		};
+		Self::from_path_delimiter(file_path, delimiter, &options)
+	}
+
+	/// Create a pipeline from a delimited file, detecting whether the delimiter is a comma, tab, semicolon or pipe. The delimiter that appears most often in the first line is used, which works for most files, including `.csv` files exported with semicolons by Excel in some locales.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path_sniff("test/Semicolons.csv")
+	///   .unwrap()
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "A,B\n1,2\n");
+	/// ```
+	pub fn from_path_sniff<P: AsRef<Path>>(file_path: P) -> Result<Self, PlError> {
+		let mut first_line = String::new();
+		let file = open_file(file_path.as_ref())?;
+		if let Err(e) = io::BufReader::new(file).read_line(&mut first_line) {
+			return Err(Error::Csv(e.into()).at_source(0));
+		}
+		let delimiter = sniff_delimiter(&first_line);
+		Self::from_path_delimiter(file_path, delimiter, &ReaderOptions::new())
+	}
+
+	fn from_path_delimiter<P: AsRef<Path>>(
+		file_path: P,
+		delimiter: u8,
+		options: &ReaderOptions,
+	) -> Result<Self, PlError> {
+		let file = open_file(file_path.as_ref())?;
+		let (delimiter_byte, input): (u8, Box<dyn io::Read>) = match options.delimiter.as_deref() {
+			Some(custom) if custom.len() == 1 => (custom.as_bytes()[0], Box::new(file)),
+			Some(custom) => (b',', Box::new(MultiDelimiterReader::new(file, custom))),
+			None => (delimiter, Box::new(file)),
+		};
+		let input = match options.max_row_size {
+			Some(max_row_size) => Box::new(RowSizeLimit::new(input, max_row_size, delimiter_byte)),
+			None => input,
 		};
-		let reader_result = ReaderBuilder::new()
-			.delimiter(delimiter)
-			.from_path(file_path);
-		match reader_result {
-			Ok(reader) => Self::from_reader(reader),
-			Err(e) => Err(Error::Csv(e).at_source(0)),
+		let mut builder = ReaderBuilder::new();
+		builder.delimiter(delimiter_byte);
+		if let Some(capacity) = options.buffer_capacity {
+			builder.buffer_capacity(capacity);
+		}
+		let reader = builder.from_reader(input);
+		let mut pipeline = Self::from_reader_options(reader, options)?;
+		pipeline.file = Some(FileSource {
+			path: file_path.as_ref().to_path_buf(),
+			delimiter,
+			options: options.clone(),
+		});
+		Ok(pipeline)
+	}
+
+	/// Create a new pipeline that reads the same file from the start, with the same delimiter and [`ReaderOptions`]. Processing steps aren't included. This lets you make multiple passes over a file without keeping it in memory.
+	///
+	/// Returns `None` if the pipeline wasn't created from a path.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let pipeline = Pipeline::from_path("test/Countries.csv").unwrap();
+	/// let rows = pipeline.reopen().unwrap().unwrap().collect_rows().unwrap();
+	/// let total = rows.len();
+	/// let csv = pipeline
+	///   .add_col("Total", move |_headers, _row| Ok(total.to_string()))
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "ID,Country,Total\n\
+	///     1,Norway,2\n\
+	///     2,Tuvalu,2\n"
+	/// );
+	/// ```
+	pub fn reopen(&self) -> Option<Result<Pipeline<'static>, PlError>> {
+		let source = self.file.as_ref()?;
+		Some(Pipeline::from_path_delimiter(
+			&source.path,
+			source.delimiter,
+			&source.options,
+		))
+	}
+
+	/// Estimate the number of rows by counting the lines of the file, without parsing it. This is fast enough to use for progress reporting, but fields that contain newlines make the estimate too high.
+	///
+	/// Returns `None` if the pipeline wasn't created from a path, or if the file can't be read.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let pipeline = Pipeline::from_path("test/Countries.csv").unwrap();
+	/// assert_eq!(pipeline.estimated_row_count(), Some(2));
+	/// ```
+	pub fn estimated_row_count(&self) -> Option<usize> {
+		let mut file = io::BufReader::new(File::open(&self.file.as_ref()?.path).ok()?);
+		let mut lines = 0;
+		let mut ends_with_newline = true;
+		loop {
+			let buffer = file.fill_buf().ok()?;
+			let len = buffer.len();
+			if len == 0 {
+				break;
+			}
+			lines += buffer.iter().filter(|byte| **byte == b'\n').count();
+			ends_with_newline = buffer[len - 1] == b'\n';
+			file.consume(len);
+		}
+		if !ends_with_newline {
+			lines += 1;
 		}
+		// The first line is the header row
+		Some(lines.saturating_sub(1))
 	}
 
 	pub fn from_rows<I: IntoIterator<Item = Row>>(records: I) -> Result<Self, PlError>
@@ -58,9 +304,7 @@ impl<'a> Pipeline<'a> {
 	{
 		let mut records = records.into_iter();
 		let headers_row = records.next().unwrap();
-		let row_iterator = records.map(|row| -> RowResult {
-			return Ok(row);
-		});
+		let row_iterator = records.map(|row| -> RowResult { Ok(row) });
 		Ok(Pipeline {
 			headers: match Headers::from_row(headers_row) {
 				Ok(headers) => headers,
@@ -69,10 +313,47 @@ impl<'a> Pipeline<'a> {
 				}
 			},
 			source: 0,
+			file: None,
+			source_label: None,
 			iterator: Box::new(row_iterator),
 		})
 	}
 
+	/// Create a pipeline from rows that have already been read, for example from a network stream or a generator. Unlike [`from_rows`](Pipeline::from_rows), the rows can be errors, which are passed through the pipeline like CSV errors.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Error, Headers, Pipeline, Row};
+	///
+	/// let headers = Headers::from_row(Row::from(vec!["Number"])).unwrap();
+	/// let rows = vec![
+	///   Ok(Row::from(vec!["1"])),
+	///   Err(Error::InvalidField("x".to_string()).at_source(0)),
+	/// ];
+	/// let mut csv = String::new();
+	/// let err = Pipeline::from_iter_with_headers(headers, rows.into_iter())
+	///   .map_col("Number", |field| Ok(field.to_string() + "0"))
+	///   .flush(csv_pipeline::Target::string(&mut csv))
+	///   .run()
+	///   .unwrap_err();
+	///
+	/// assert!(matches!(err.error, Error::InvalidField(_)));
+	/// assert_eq!(csv, "Number\n10\n");
+	/// ```
+	pub fn from_iter_with_headers<I>(headers: Headers, iter: I) -> Self
+	where
+		I: Iterator<Item = RowResult> + 'a,
+	{
+		Pipeline {
+			headers,
+			source: 0,
+			file: None,
+			source_label: None,
+			iterator: Box::new(iter),
+		}
+	}
+
 	/// Merge multiple source pipelines into one. The source pipelines must have identical headers, otherwise the pipelie will return a [`MismatchedHeaders`](Error::MismatchedHeaders) error  returned.
 	///
 	/// ## Example
@@ -100,19 +381,86 @@ impl<'a> Pipeline<'a> {
 			Some(ref pipeline) => pipeline.headers.clone(),
 			None => Headers::new(),
 		};
+		let label = Rc::new(RefCell::new(match current {
+			Some(ref pipeline) => pipeline.label(0),
+			None => String::new(),
+		}));
 		Pipeline {
 			headers: headers.clone(),
 			source: 0,
+			file: None,
+			source_label: Some(label.clone()),
 			iterator: Box::new(PipelinesChain {
 				pipelines,
 				current: current.map(|p| p.build()),
 				index: 0,
 				headers,
+				label,
 			}),
 		}
 	}
 
-	/// Adds a column with values computed from the closure for each row.
+	/// The path of the file the pipeline reads from, or otherwise the source index
+	pub(crate) fn label(&self, index: usize) -> String {
+		match &self.file {
+			Some(file) => file.path.display().to_string(),
+			None => index.to_string(),
+		}
+	}
+
+	/// Merge multiple source pipelines into one, matching up columns by name. The result has the union of every source's columns, in the order of the first source followed by any new columns from the other sources. Each source's rows are reordered to match, and columns that a source doesn't have are filled with empty fields.
+	///
+	/// Unlike [`Pipeline::from_pipelines`], the sources don't need identical headers.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::union_by_name(vec![
+	///   Pipeline::from_str("A,B\n1,2\n").unwrap(),
+	///   Pipeline::from_str("C,B\n4,3\n").unwrap(),
+	/// ])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "A,B,C\n\
+	///     1,2,\n\
+	///     ,3,4\n"
+	/// );
+	/// ```
+	pub fn union_by_name<I>(pipelines: I) -> Self
+	where
+		I: IntoIterator<Item = Pipeline<'a>>,
+	{
+		let pipelines: Vec<Pipeline<'a>> = pipelines.into_iter().collect();
+		let mut headers = Headers::new();
+		for pipeline in &pipelines {
+			for col in &pipeline.headers {
+				headers.push_field(col);
+			}
+		}
+		let aligned: Vec<Pipeline<'a>> = pipelines
+			.into_iter()
+			.map(|mut pipeline| {
+				pipeline.iterator = Box::new(Align {
+					iterator: pipeline.iterator,
+					columns: headers
+						.into_iter()
+						.map(|col| (col.to_string(), pipeline.headers.get_index(col)))
+						.collect(),
+					source: pipeline.source,
+				});
+				pipeline.headers = headers.clone();
+				pipeline
+			})
+			.collect();
+		Self::from_pipelines(aligned)
+	}
+
+	/// Adds a column with values computed from the closure for each row. If a column with this name already exists, the pipeline returns a [`DuplicateColumn`](Error::DuplicateColumn) error. This applies to every method that adds a column using a closure, like [`flag_col`](Pipeline::flag_col) and [`add_const_col`](Pipeline::add_const_col).
 	///
 	/// ## Example
 	///
@@ -133,7 +481,9 @@ impl<'a> Pipeline<'a> {
 	where
 		F: FnMut(&Headers, &Row) -> Result<String, Error> + 'a,
 	{
-		self.headers.push_field(name);
+		if !self.headers.push_field(name) {
+			return self.fail_with(Error::DuplicateColumn(name.to_string()));
+		}
 		self.iterator = Box::new(AddCol {
 			iterator: self.iterator,
 			f: get_value,
@@ -143,7 +493,7 @@ impl<'a> Pipeline<'a> {
 		self
 	}
 
-	/// Maps each row.
+	/// Adds a column with the same value in every row, for example to tag the rows with a batch ID.
 	///
 	/// ## Example
 	///
@@ -152,217 +502,408 @@ impl<'a> Pipeline<'a> {
 	///
 	/// let csv = Pipeline::from_path("test/AB.csv")
 	///   .unwrap()
-	///   .map(|headers, row| {
-	///     Ok(row.into_iter().map(|field| field.to_string() + "0").collect())
-	///   })
+	///   .add_const_col("Batch", "7")
 	///   .collect_into_string()
 	///   .unwrap();
 	///
-	/// assert_eq!(csv, "A,B\n10,20\n"
-	/// );
+	/// assert_eq!(csv, "A,B,Batch\n1,2,7\n");
 	/// ```
-	pub fn map<F>(mut self, get_row: F) -> Self
-	where
-		F: FnMut(&Headers, Row) -> Result<Row, Error> + 'a,
-	{
-		self.iterator = Box::new(MapRow {
-			iterator: self.iterator,
-			f: get_row,
-			source: self.source,
-			headers: self.headers.clone(),
-		});
-		self
+	pub fn add_const_col(self, name: &str, value: &str) -> Self {
+		let value = value.to_string();
+		self.add_col(name, move |_headers, _row| Ok(value.clone()))
 	}
 
-	/// Maps each field of a column.
+	/// Adds a column with the source of each row: the path of the file for pipelines created from a path, or otherwise the source index. For pipelines merged using [`from_pipelines`](Pipeline::from_pipelines) or [`union_by_name`](Pipeline::union_by_name), each row gets the path or index of the pipeline it came from.
+	///
+	/// For merged pipelines, add this step before steps that read rows ahead or group them, like [`sort_by`](Pipeline::sort_by), [`lead_col`](Pipeline::lead_col), [`branch`](Pipeline::branch) or [`transform_into`](Pipeline::transform_into), since those lose track of where rows came from. After them, every row gets the source index `0`.
 	///
 	/// ## Example
 	///
 	/// ```
 	/// use csv_pipeline::Pipeline;
 	///
-	/// let csv = Pipeline::from_path("test/Countries.csv")
-	///   .unwrap()
-	///   .map_col("Country", |field| Ok(field.to_uppercase()))
+	/// let csv = Pipeline::from_pipelines(vec![
+	///   Pipeline::from_path("test/AB.csv").unwrap(),
+	///   Pipeline::from_str("A,B\n3,4\n").unwrap(),
+	/// ])
+	///   .add_source_col("Source")
 	///   .collect_into_string()
 	///   .unwrap();
 	///
-	/// assert_eq!(
-	///   csv,
-	///   "ID,Country\n\
-	///     1,NORWAY\n\
-	///     2,TUVALU\n"
-	/// );
+	/// assert_eq!(csv, "A,B,Source\n1,2,test/AB.csv\n3,4,1\n");
 	/// ```
-	pub fn map_col<F>(mut self, col: &str, get_value: F) -> Self
-	where
-		F: FnMut(&str) -> Result<String, Error> + 'a,
-	{
-		self.iterator = Box::new(MapCol {
-			iterator: self.iterator,
-			f: get_value,
-			name: col.to_string(),
-			source: self.source,
-			index: self.headers.get_index(col),
-		});
-		self
+	pub fn add_source_col(self, name: &str) -> Self {
+		match self.source_label.clone() {
+			Some(label) => self.add_col(name, move |_headers, _row| Ok(label.borrow().clone())),
+			None => {
+				let label = self.label(self.source);
+				self.add_const_col(name, &label)
+			}
+		}
 	}
 
-	/// Filter rows using the provided closure.
+	/// Adds a column with the value of an environment variable in every row. The variable is read when this step is added. If it isn't set, the pipeline returns a [`MissingEnvVar`](Error::MissingEnvVar) error.
 	///
 	/// ## Example
 	///
 	/// ```
 	/// use csv_pipeline::Pipeline;
 	///
-	/// let csv = Pipeline::from_path("test/Countries.csv")
+	/// std::env::set_var("CSV_PIPELINE_BATCH", "7");
+	/// let csv = Pipeline::from_path("test/AB.csv")
 	///   .unwrap()
-	///   .filter(|headers, row| {
-	///     let country = headers.get_field(&row, "Country").unwrap();
-	///     country == "Tuvalu"
-	///   })
+	///   .add_env_col("Batch", "CSV_PIPELINE_BATCH")
 	///   .collect_into_string()
 	///   .unwrap();
 	///
-	/// assert_eq!(
-	///   csv,
-	///   "ID,Country\n\
-	///     2,Tuvalu\n"
-	/// );
+	/// assert_eq!(csv, "A,B,Batch\n1,2,7\n");
 	/// ```
-	pub fn filter<F>(mut self, get_row: F) -> Self
-	where
-		F: FnMut(&Headers, &Row) -> bool + 'a,
-	{
-		self.iterator = Box::new(Filter {
-			iterator: self.iterator,
-			f: get_row,
-			source: self.source,
-			headers: self.headers.clone(),
-		});
-		self
+	pub fn add_env_col(self, name: &str, var: &str) -> Self {
+		match std::env::var(var) {
+			Ok(value) => self.add_const_col(name, &value),
+			Err(_) => self
+				.add_const_col(name, "")
+				.fail_with(Error::MissingEnvVar(var.to_string())),
+		}
 	}
 
-	/// Filter rows based on the field of the specified column, using the provided closure.
+	/// Like [`Pipeline::add_col`], but errors from the closure are wrapped in an [`AddCol`](Error::AddCol) error, which includes the column name and the index of the row. The index counts the rows that reach this step, starting at 0.
 	///
 	/// ## Example
 	///
 	/// ```
-	/// use csv_pipeline::Pipeline;
+	/// use csv_pipeline::{Error, Pipeline};
 	///
-	/// let csv = Pipeline::from_path("test/Countries.csv")
+	/// let err = Pipeline::from_str("Amount\n1\nx\n")
 	///   .unwrap()
-	///   .filter_col("Country", |country| country == "Tuvalu")
+	///   .add_col_ctx("Double", |headers, row| {
+	///     let amount = headers.get_field(row, "Amount").unwrap();
+	///     match amount.parse::<i64>() {
+	///       Ok(amount) => Ok((amount * 2).to_string()),
+	///       Err(_) => Err(Error::InvalidField(amount.to_string())),
+	///     }
+	///   })
 	///   .collect_into_string()
-	///   .unwrap();
+	///   .unwrap_err();
 	///
-	/// assert_eq!(
-	///   csv,
-	///   "ID,Country\n\
-	///     2,Tuvalu\n"
-	/// );
+	/// match err.error {
+	///   Error::AddCol(col, index, error) => {
+	///     assert_eq!(col, "Double");
+	///     assert_eq!(index, 1);
+	///     assert!(matches!(*error, Error::InvalidField(_)));
+	///   }
+	///   _ => panic!("Expected an AddCol error"),
+	/// }
 	/// ```
-	pub fn filter_col<F>(mut self, name: &str, get_row: F) -> Self
+	pub fn add_col_ctx<F>(self, name: &str, mut get_value: F) -> Self
 	where
-		F: FnMut(&str) -> bool + 'a,
+		F: FnMut(&Headers, &Row) -> Result<String, Error> + 'a,
 	{
-		self.iterator = Box::new(FilterCol {
-			name: name.to_string(),
-			iterator: self.iterator,
-			f: get_row,
-			source: self.source,
-			headers: self.headers.clone(),
-		});
-		self
+		let col = name.to_string();
+		let mut index = 0;
+		self.add_col(name, move |headers, row| {
+			let result =
+				get_value(headers, row).map_err(|e| Error::AddCol(col.clone(), index, Box::new(e)));
+			index += 1;
+			result
+		})
 	}
 
-	/// Pick which columns to output, in the specified order. Panics if duplicate colums are specified.
+	/// Like [`Pipeline::add_col`], but the closure is only called once for each distinct key, where the key is the fields of `key_cols`. Other rows with the same key reuse the cached value. This is useful when the closure is expensive, like a lookup in another system. Errors aren't cached.
+	///
+	/// By default, every distinct key is kept in memory. If `max_entries` is set, the least recently used keys are removed from the cache when it's full, so their values may be computed again. With `Some(0)`, nothing is cached.
 	///
 	/// ## Example
 	///
 	/// ```
 	/// use csv_pipeline::Pipeline;
 	///
-	/// let csv = Pipeline::from_path("test/AB.csv")
+	/// let mut calls = 0;
+	/// let csv = Pipeline::from_str("City\nOslo\nBergen\nOslo\n")
 	///   .unwrap()
-	///   .select(vec!["B"])
+	///   .add_col_cached("Length", vec!["City"], None, |headers, row| {
+	///     calls += 1;
+	///     Ok(headers.get_field(row, "City").unwrap().len().to_string())
+	///   })
 	///   .collect_into_string()
 	///   .unwrap();
 	///
-	/// assert_eq!(csv, "B\n2\n");
+	/// assert_eq!(csv, "City,Length\nOslo,4\nBergen,6\nOslo,4\n");
+	/// assert_eq!(calls, 2);
 	/// ```
-	pub fn select(mut self, columns: Vec<&str>) -> Self {
-		let new_header_row = Row::from(columns.clone());
-		self.iterator = Box::new(Select {
+	pub fn add_col_cached<F>(
+		self,
+		name: &str,
+		key_cols: Vec<&str>,
+		max_entries: Option<usize>,
+		mut get_value: F,
+	) -> Self
+	where
+		F: FnMut(&Headers, &Row) -> Result<String, Error> + 'a,
+	{
+		let columns: Vec<_> = key_cols
+			.into_iter()
+			.map(|col| (col.to_string(), self.headers.get_index(col)))
+			.collect();
+		let mut cache: LinkedHashMap<Vec<String>, String> = LinkedHashMap::new();
+		self.add_col(name, move |headers, row| {
+			let key = row_key(row, &columns)?;
+			if let Some(value) = cache.get_refresh(&key) {
+				return Ok(value.clone());
+			}
+			let value = get_value(headers, row)?;
+			match max_entries {
+				Some(0) => return Ok(value),
+				Some(max) if cache.len() >= max => {
+					cache.pop_front();
+				}
+				_ => {}
+			}
+			cache.insert(key, value.clone());
+			Ok(value)
+		})
+	}
+
+	/// Adds a column that is `true` for rows that match the predicate, and `false` for other rows. Unlike [`Pipeline::filter`], no rows are removed.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .flag_col("Nordic", |headers, row| headers.get_field(row, "Country") == Some("Norway"))
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "ID,Country,Nordic\n\
+	///     1,Norway,true\n\
+	///     2,Tuvalu,false\n"
+	/// );
+	/// ```
+	pub fn flag_col<F>(self, name: &str, mut predicate: F) -> Self
+	where
+		F: FnMut(&Headers, &Row) -> bool + 'a,
+	{
+		self.add_col(name, move |headers, row| {
+			Ok(predicate(headers, row).to_string())
+		})
+	}
+
+	/// Adds a column with the value of the first case whose predicate matches the row, or `default` if none match, like SQL's `CASE WHEN`.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Predicate};
+	///
+	/// let amount = |row: &csv::StringRecord| row[0].parse::<u32>().unwrap();
+	/// let cases: Vec<(Predicate, &str)> = vec![
+	///   (Box::new(move |_, row| amount(row) >= 1000), "large"),
+	///   (Box::new(move |_, row| amount(row) >= 100), "medium"),
+	/// ];
+	/// let csv = Pipeline::from_str("Amount\n5\n250\n5000\n")
+	///   .unwrap()
+	///   .case_col("Size", cases, "small")
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Amount,Size\n\
+	///     5,small\n\
+	///     250,medium\n\
+	///     5000,large\n"
+	/// );
+	/// ```
+	pub fn case_col(self, name: &str, cases: Vec<(Predicate<'a>, &str)>, default: &str) -> Self {
+		let mut cases: Vec<(Predicate<'a>, String)> = cases
+			.into_iter()
+			.map(|(predicate, value)| (predicate, value.to_string()))
+			.collect();
+		let default = default.to_string();
+		self.add_col(name, move |headers, row| {
+			for (predicate, value) in &mut cases {
+				if predicate(headers, row) {
+					return Ok(value.clone());
+				}
+			}
+			Ok(default.clone())
+		})
+	}
+
+	/// Adds a column that joins the fields of the specified columns with a separator. The original columns are kept.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_str("First,Last\nAda,Lovelace\n")
+	///   .unwrap()
+	///   .concat_col("Name", vec!["First", "Last"], " ")
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "First,Last,Name\nAda,Lovelace,Ada Lovelace\n");
+	/// ```
+	pub fn concat_col(self, name: &str, cols: Vec<&str>, sep: &str) -> Self {
+		let columns: Vec<(String, Option<usize>)> = cols
+			.into_iter()
+			.map(|col| (col.to_string(), self.headers.get_index(col)))
+			.collect();
+		let sep = sep.to_string();
+		self.add_col(name, move |_headers, row| {
+			let mut fields = Vec::with_capacity(columns.len());
+			for (col, index) in &columns {
+				match index.and_then(|index| row.get(index)) {
+					Some(field) => fields.push(field),
+					None => return Err(Error::MissingColumn(col.clone())),
+				}
+			}
+			Ok(fields.join(&sep))
+		})
+	}
+
+	/// Map each row result using a closure that can keep state between rows, starting from `init`. Unlike the other steps, the closure also receives errors, so it can handle or replace them. Useful for cross-row logic that doesn't have a dedicated method.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// // Number the rows
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .stateful(0, |row, count| {
+	///     let row = row?;
+	///     *count += 1;
+	///     Ok(row.into_iter().map(|field| format!("{}{}", field, count)).collect())
+	///   })
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "ID,Country\n\
+	///     11,Norway1\n\
+	///     22,Tuvalu2\n"
+	/// );
+	/// ```
+	pub fn stateful<S, F>(mut self, init: S, f: F) -> Self
+	where
+		S: 'a,
+		F: FnMut(RowResult, &mut S) -> RowResult + 'a,
+	{
+		self.iterator = Box::new(Stateful {
 			iterator: self.iterator,
-			columns: columns.into_iter().map(String::from).collect(),
-			source: self.source,
-			headers: self.headers.clone(),
+			state: init,
+			f,
 		});
-		self.headers = Headers::from_row(new_header_row).unwrap();
 		self
 	}
 
-	/// Panics if a new name already exists
+	/// Apply a function to the pipeline. This is just function application, `f(self)`, but it lets you package a sequence of steps as a reusable function and use it without breaking the method chain.
 	///
 	/// ## Example
 	///
 	/// ```
 	/// use csv_pipeline::Pipeline;
 	///
-	/// let csv = Pipeline::from_path("test/AB.csv")
+	/// fn clean(pipeline: Pipeline) -> Pipeline {
+	///   pipeline
+	///     .map_col("Country", |country| Ok(country.trim().to_string()))
+	///     .map_col("Country", |country| Ok(country.to_uppercase()))
+	/// }
+	///
+	/// let csv = Pipeline::from_str("ID,Country\n1, Norway \n")
 	///   .unwrap()
-	///   .rename_col("A", "X")
+	///   .apply(clean)
 	///   .collect_into_string()
 	///   .unwrap();
 	///
-	/// assert_eq!(csv, "X,B\n1,2\n");
+	/// assert_eq!(csv, "ID,Country\n1,NORWAY\n");
 	/// ```
-	pub fn rename_col(mut self, from: &str, to: &str) -> Self {
-		match self.headers.rename(from, to) {
-			Ok(()) => (),
-			Err(e) => panic!("Error renaming column in source {}: {}", self.source, e),
-		};
-		self
+	pub fn apply<F>(self, f: F) -> Self
+	where
+		F: FnOnce(Self) -> Self,
+	{
+		f(self)
 	}
 
-	/// Panics if a new name already exists
+	/// Process the rows that match the predicate with the `then` steps, and the other rows with the `otherwise` steps, then merge the results back into one pipeline. Both branches must result in the same headers, otherwise the pipeline returns a [`MismatchedHeaders`](Error::MismatchedHeaders) error. Errors from before the branch go through the `then` steps.
+	///
+	/// Rows keep their order, even if a branch filters rows, unless one of the branches has a step that buffers or reorders rows, like [`sort_by`](Pipeline::sort_by).
 	///
 	/// ## Example
 	///
 	/// ```
 	/// use csv_pipeline::Pipeline;
 	///
-	/// let csv = Pipeline::from_path("test/AB.csv")
+	/// let csv = Pipeline::from_str("Type,Amount\nA,1\nB,2\nA,3\n")
 	///   .unwrap()
-	///   .rename_cols(|i, name| {
-	///     match name {
-	///       "A" => "X",
-	///       name => name,
-	///     }
-	///   })
+	///   .branch(
+	///     |headers, row| headers.get_field(row, "Type") == Some("A"),
+	///     |pipeline| pipeline.map_col("Amount", |amount| Ok(amount.to_string() + "0")),
+	///     |pipeline| pipeline.map_col("Amount", |amount| Ok(format!("-{}", amount))),
+	///   )
 	///   .collect_into_string()
 	///   .unwrap();
 	///
-	/// assert_eq!(csv, "X,B\n1,2\n");
+	/// assert_eq!(csv, "Type,Amount\nA,10\nB,-2\nA,30\n");
 	/// ```
-	pub fn rename_cols<R>(mut self, mut get_name: R) -> Self
+	pub fn branch<P, T, E>(self, predicate: P, then: T, otherwise: E) -> Self
 	where
-		R: FnMut(usize, &str) -> &str,
+		P: FnMut(&Headers, &Row) -> bool + 'a,
+		T: FnOnce(Pipeline<'a>) -> Pipeline<'a>,
+		E: FnOnce(Pipeline<'a>) -> Pipeline<'a>,
 	{
-		let mut new_headers = Headers::new();
-		for (i, name) in self.headers.into_iter().enumerate().borrow_mut() {
-			let new_name = get_name(i, name);
-			match new_headers.push_field(new_name) {
-				true => (),
-				false => panic!("New column name already exists"),
-			}
+		let shared = Rc::new(RefCell::new(BranchShared {
+			iterator: self.iterator,
+			predicate,
+			headers: self.headers.clone(),
+			queues: [VecDeque::new(), VecDeque::new()],
+			position: 0,
+			taken: [0, 0],
+		}));
+		let input = |branch| Pipeline {
+			headers: self.headers.clone(),
+			source: self.source,
+			file: None,
+			source_label: None,
+			iterator: Box::new(BranchInput {
+				shared: shared.clone(),
+				branch,
+			}),
+		};
+		let then = then(input(0));
+		let otherwise = otherwise(input(1));
+		let pipeline = Pipeline {
+			headers: then.headers.clone(),
+			source: self.source,
+			file: self.file,
+			source_label: None,
+			iterator: Box::new(Branch {
+				shared,
+				branches: [then.iterator, otherwise.iterator],
+				done: [false, false],
+				pending: [None, None],
+			}),
+		};
+		if then.headers.get_row() != otherwise.headers.get_row() {
+			let headers = (
+				then.headers.get_row().clone(),
+				otherwise.headers.get_row().clone(),
+			);
+			return pipeline.fail_with(Error::MismatchedHeaders(headers.0, headers.1));
 		}
-		self.headers = new_headers;
-		self
+		pipeline
 	}
 
-	/// Group and reduce rows into the provided format. Panics if the transform results in duplicate column names.
+	/// Maps each row.
 	///
 	/// ## Example
 	///
@@ -371,119 +912,2598 @@ impl<'a> Pipeline<'a> {
 	///
 	/// let csv = Pipeline::from_path("test/AB.csv")
 	///   .unwrap()
-	///   .rename_cols(|i, name| {
-	///     match name {
-	///       "A" => "X",
-	///       name => name,
-	///     }
+	///   .map(|headers, row| {
+	///     Ok(row.into_iter().map(|field| field.to_string() + "0").collect())
 	///   })
 	///   .collect_into_string()
 	///   .unwrap();
 	///
-	/// assert_eq!(csv, "X,B\n1,2\n");
+	/// assert_eq!(csv, "A,B\n10,20\n"
+	/// );
 	/// ```
-	pub fn transform_into<T>(self, mut get_transformers: T) -> Self
+	pub fn map<F>(mut self, get_row: F) -> Self
 	where
-		T: FnMut() -> Vec<Box<dyn Transform>> + 'a,
+		F: FnMut(&Headers, Row) -> Result<Row, Error> + 'a,
 	{
-		let hashers = get_transformers();
-		let names: Vec<_> = hashers.iter().map(|hasher| hasher.name()).collect();
-		Pipeline {
-			headers: Headers::from_row(Row::from(names)).unwrap(),
+		self.iterator = Box::new(MapRow {
+			iterator: self.iterator,
+			f: get_row,
 			source: self.source,
-			iterator: Box::new(TransformInto {
-				iterator: self.iterator,
-				groups: LinkedHashMap::new(),
-				hashers: get_transformers(),
-				get_transformers,
-				source: self.source,
-				headers: self.headers.clone(),
-			}),
-		}
+			headers: self.headers.clone(),
+		});
+		self
 	}
 
-	/// Do your own validation on each row.
-	pub fn validate<F>(mut self, f: F) -> Self
+	/// Maps each row into zero or more rows.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Row};
+	///
+	/// let source = "\
+	///   Start,End\n\
+	///   1,3\n\
+	///   7,8\n";
+	/// let reader = csv::Reader::from_reader(source.as_bytes());
+	/// let csv = Pipeline::from_reader(reader)
+	///   .unwrap()
+	///   .flat_map(|headers, row| {
+	///     let start: u32 = headers.get_field(&row, "Start").unwrap().parse().unwrap();
+	///     let end: u32 = headers.get_field(&row, "End").unwrap().parse().unwrap();
+	///     Ok((start..=end).map(|n| Row::from(vec![n.to_string(), end.to_string()])).collect())
+	///   })
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Start,End\n\
+	///     1,3\n\
+	///     2,3\n\
+	///     3,3\n\
+	///     7,8\n\
+	///     8,8\n"
+	/// );
+	/// ```
+	pub fn flat_map<F>(mut self, get_rows: F) -> Self
 	where
-		F: FnMut(&Headers, &Row) -> Result<(), Error> + 'a,
+		F: FnMut(&Headers, Row) -> Result<Vec<Row>, Error> + 'a,
 	{
-		self.iterator = Box::new(Validate {
+		self.iterator = Box::new(FlatMap {
 			iterator: self.iterator,
-			f,
+			f: get_rows,
 			source: self.source,
 			headers: self.headers.clone(),
+			buffer: Vec::new().into_iter(),
 		});
 		self
 	}
 
-	/// Do your own validation on the fields in a column.
-	pub fn validate_col<F>(mut self, name: &str, f: F) -> Self
+	/// Turn the `value_cols` columns into rows, from wide to long format. Each input row becomes one row per value column, with the `id_cols` columns, a `var_name` column with the name of the value column, and a `value_name` column with its field. Other columns are removed. Rows are returned as they are read, so nothing is buffered.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_str("Product,Jan,Feb\nA,1,2\nB,3,4\n")
+	///   .unwrap()
+	///   .unpivot_fixed(vec!["Product"], vec!["Jan", "Feb"], "Month", "Sales")
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Product,Month,Sales\n\
+	///     A,Jan,1\n\
+	///     A,Feb,2\n\
+	///     B,Jan,3\n\
+	///     B,Feb,4\n"
+	/// );
+	/// ```
+	pub fn unpivot_fixed(
+		self,
+		id_cols: Vec<&str>,
+		value_cols: Vec<&str>,
+		var_name: &str,
+		value_name: &str,
+	) -> Self {
+		let columns = |cols: &[&str]| -> Vec<(String, Option<usize>)> {
+			cols.iter()
+				.map(|col| (col.to_string(), self.headers.get_index(col)))
+				.collect()
+		};
+		let id_columns = columns(&id_cols);
+		let value_columns = columns(&value_cols);
+		let mut names: Vec<String> = id_cols.iter().map(|col| col.to_string()).collect();
+		names.push(var_name.to_string());
+		names.push(value_name.to_string());
+		self.flat_map(move |_headers, row| {
+			let ids = row_key(&row, &id_columns)?;
+			let values = row_key(&row, &value_columns)?;
+			let rows = value_columns.iter().zip(values).map(|((name, _), value)| {
+				let mut output = Row::with_capacity(0, ids.len() + 2);
+				for id in &ids {
+					output.push_field(id);
+				}
+				output.push_field(name);
+				output.push_field(&value);
+				output
+			});
+			Ok(rows.collect())
+		})
+		.with_output_headers(names)
+	}
+
+	/// Maps each field of a column.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .map_col("Country", |field| Ok(field.to_uppercase()))
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "ID,Country\n\
+	///     1,NORWAY\n\
+	///     2,TUVALU\n"
+	/// );
+	/// ```
+	pub fn map_col<F>(mut self, col: &str, get_value: F) -> Self
 	where
-		F: FnMut(&str) -> Result<(), Error> + 'a,
+		F: FnMut(&str) -> Result<String, Error> + 'a,
 	{
-		self.iterator = Box::new(ValidateCol {
-			name: name.to_string(),
+		self.iterator = Box::new(MapCol {
 			iterator: self.iterator,
-			f,
+			f: get_value,
+			name: col.to_string(),
 			source: self.source,
-			headers: self.headers.clone(),
+			index: self.headers.get_index(col),
 		});
 		self
 	}
 
-	/// Write to the specified [`Target`].
+	/// Maps each field of multiple columns using the same closure.
 	///
 	/// ## Example
 	///
 	/// ```
-	/// use csv_pipeline::{Pipeline, Target};
+	/// use csv_pipeline::Pipeline;
 	///
-	/// let mut csv = String::new();
-	/// Pipeline::from_path("test/AB.csv")
+	/// let csv = Pipeline::from_str("A,B,C\n a , b , c \n")
 	///   .unwrap()
-	///   .flush(Target::string(&mut csv))
-	///   .run()
+	///   .map_cols(vec!["A", "C"], |field| Ok(field.trim().to_string()))
+	///   .collect_into_string()
 	///   .unwrap();
 	///
-	/// assert_eq!(csv, "A,B\n1,2\n");
+	/// assert_eq!(csv, "A,B,C\na, b ,c\n");
 	/// ```
-	pub fn flush(mut self, target: impl Target + 'a) -> Self {
-		let flush = Flush::new(self.iterator, target, self.source, self.headers.clone());
-		self.iterator = Box::new(flush);
+	pub fn map_cols<F>(mut self, cols: Vec<&str>, get_value: F) -> Self
+	where
+		F: FnMut(&str) -> Result<String, Error> + 'a,
+	{
+		self.iterator = Box::new(MapCols {
+			iterator: self.iterator,
+			f: get_value,
+			columns: cols
+				.into_iter()
+				.map(|col| (col.to_string(), self.headers.get_index(col)))
+				.collect(),
+			source: self.source,
+		});
 		self
 	}
 
-	/// Turn the pipeline into an iterator.
-	/// You can also do this using `pipeline.into_iter()`.
-	pub fn build(self) -> PipelineIter<'a> {
-		PipelineIter {
-			headers: self.headers,
-			iterator: Box::new(self.iterator),
-		}
+	/// Set the field of a column to `value` in rows that match the predicate. Other rows are left unchanged.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_str("Amount,Status\n500,ok\n2000,ok\n")
+	///   .unwrap()
+	///   .update_where(
+	///     |headers, row| headers.get_field(row, "Amount").unwrap().parse::<i32>().unwrap() > 1000,
+	///     "Status",
+	///     "flagged",
+	///   )
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Amount,Status\n\
+	///     500,ok\n\
+	///     2000,flagged\n"
+	/// );
+	/// ```
+	pub fn update_where<P>(self, predicate: P, col: &str, value: &str) -> Self
+	where
+		P: FnMut(&Headers, &Row) -> bool + 'a,
+	{
+		let value = value.to_string();
+		self.update_where_with(predicate, col, move |_| Ok(value.clone()))
+	}
+
+	/// Like [`Pipeline::update_where`], but the new value is computed from the current field using a closure.
+	pub fn update_where_with<P, F>(mut self, predicate: P, col: &str, get_value: F) -> Self
+	where
+		P: FnMut(&Headers, &Row) -> bool + 'a,
+		F: FnMut(&str) -> Result<String, Error> + 'a,
+	{
+		self.iterator = Box::new(UpdateWhere {
+			iterator: self.iterator,
+			predicate,
+			f: get_value,
+			headers: self.headers.clone(),
+			name: col.to_string(),
+			source: self.source,
+			index: self.headers.get_index(col),
+		});
+		self
+	}
+
+	/// Parse the fields of a column as key-value pairs like `a=1;b=2`, and add a column for each of the specified keys. Keys that a field doesn't have result in empty fields, and other keys are ignored. Panics if a key's column already exists.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_str("ID,Tags\n1,a=1;b=2\n2,b=3;c=4\n")
+	///   .unwrap()
+	///   .expand_kv_keys("Tags", vec!["a", "b"], ";", "=")
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "ID,Tags,a,b\n\
+	///     1,a=1;b=2,1,2\n\
+	///     2,b=3;c=4,,3\n"
+	/// );
+	/// ```
+	pub fn expand_kv_keys(self, col: &str, keys: Vec<&str>, pair_sep: &str, kv_sep: &str) -> Self {
+		let result = self.try_expand_kv_keys(col, keys, pair_sep, kv_sep);
+		or_panic_duplicate(result, |key| format!("Column already exists: {}", key))
+	}
+
+	/// Like [`Pipeline::expand_kv_keys`], but returns a [`DuplicateColumn`](Error::DuplicateColumn) error instead of panicking if a key's column already exists.
+	pub fn try_expand_kv_keys(
+		mut self,
+		col: &str,
+		keys: Vec<&str>,
+		pair_sep: &str,
+		kv_sep: &str,
+	) -> Result<Self, PlError> {
+		for key in &keys {
+			if !self.headers.push_field(key) {
+				return Err(Error::DuplicateColumn(key.to_string()).at_source(self.source));
+			}
+		}
+		self.iterator = Box::new(ExpandKv {
+			iterator: self.iterator,
+			name: col.to_string(),
+			index: self.headers.get_index(col),
+			keys: keys.into_iter().map(String::from).collect(),
+			pair_sep: pair_sep.to_string(),
+			kv_sep: kv_sep.to_string(),
+			query_string: false,
+			source: self.source,
+		});
+		Ok(self)
+	}
+
+	/// Parse the fields of a column as URLs or query strings, and add a column for each of the specified parameters, named with the prefix followed by the parameter name. Keys and values are percent-decoded. Parameters that a field doesn't have result in empty fields. Panics if a parameter's column already exists.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let source = "\
+	///   URL\n\
+	///   https://example.com/?utm_source=news&q=csv+pipeline%21#top\n\
+	///   https://example.com/about\n";
+	/// let csv = Pipeline::from_str(source)
+	///   .unwrap()
+	///   .parse_query_string("URL", vec!["utm_source", "q"], "param_")
+	///   .select(vec!["param_utm_source", "param_q"])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "param_utm_source,param_q\n\
+	///     news,csv pipeline!\n\
+	///     ,\n"
+	/// );
+	/// ```
+	pub fn parse_query_string(self, col: &str, params: Vec<&str>, prefix: &str) -> Self {
+		let result = self.try_parse_query_string(col, params, prefix);
+		or_panic_duplicate(result, |name| format!("Column already exists: {}", name))
+	}
+
+	/// Like [`Pipeline::parse_query_string`], but returns a [`DuplicateColumn`](Error::DuplicateColumn) error instead of panicking if a parameter's column already exists.
+	pub fn try_parse_query_string(
+		mut self,
+		col: &str,
+		params: Vec<&str>,
+		prefix: &str,
+	) -> Result<Self, PlError> {
+		for param in &params {
+			let name = format!("{}{}", prefix, param);
+			if !self.headers.push_field(&name) {
+				return Err(Error::DuplicateColumn(name).at_source(self.source));
+			}
+		}
+		self.iterator = Box::new(ExpandKv {
+			iterator: self.iterator,
+			name: col.to_string(),
+			index: self.headers.get_index(col),
+			keys: params.into_iter().map(String::from).collect(),
+			pair_sep: "&".to_string(),
+			kv_sep: "=".to_string(),
+			query_string: true,
+			source: self.source,
+		});
+		Ok(self)
+	}
+
+	/// Like [`Pipeline::expand_kv_keys`], but adds a column for every key found, in the order they're first seen. To find the keys, every row is read into memory when this is called, so any error before this step is returned here. If a key's column already exists, a [`DuplicateColumn`](Error::DuplicateColumn) error is returned.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_str("ID,Tags\n1,a=1;b=2\n2,b=3;c=4\n")
+	///   .unwrap()
+	///   .expand_kv("Tags", ";", "=")
+	///   .unwrap()
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "ID,Tags,a,b,c\n\
+	///     1,a=1;b=2,1,2,\n\
+	///     2,b=3;c=4,,3,4\n"
+	/// );
+	/// ```
+	pub fn expand_kv(mut self, col: &str, pair_sep: &str, kv_sep: &str) -> Result<Self, PlError> {
+		let source = self.source;
+		let index = match self.headers.get_index(col) {
+			Some(index) => index,
+			None => return Err(Error::MissingColumn(col.to_string()).at_source(source)),
+		};
+		let headers = self.headers.clone();
+		let file = self.file.take();
+		let rows: Vec<Row> = self.build().collect::<Result<_, _>>()?;
+		let mut keys: Vec<&str> = Vec::new();
+		for row in &rows {
+			let field = match row.get(index) {
+				Some(field) => field,
+				None => return Err(Error::MissingColumn(col.to_string()).at_source(source)),
+			};
+			for (key, _) in parse_kv(field, pair_sep, kv_sep) {
+				if !keys.contains(&key) {
+					keys.push(key);
+				}
+			}
+		}
+		let keys: Vec<String> = keys.into_iter().map(String::from).collect();
+		let pipeline = Pipeline {
+			headers,
+			source,
+			file,
+			source_label: None,
+			iterator: Box::new(rows.into_iter().map(Ok)),
+		};
+		pipeline.try_expand_kv_keys(
+			col,
+			keys.iter().map(|k| k.as_str()).collect(),
+			pair_sep,
+			kv_sep,
+		)
+	}
+
+	/// Parse the fields of a column as `f64` and clamp them to `[min, max]`. Fields that aren't numbers result in an [`InvalidField`](Error::InvalidField) error. Panics if `min` is greater than `max`.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_str("Reading\n-5\n42.5\n180\n")
+	///   .unwrap()
+	///   .clamp_col("Reading", 0.0, 100.0)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "Reading\n0\n42.5\n100\n");
+	/// ```
+	pub fn clamp_col(self, col: &str, min: f64, max: f64) -> Self {
+		assert!(min <= max, "min must not be greater than max");
+		self.map_col(col, move |field| match field.parse::<f64>() {
+			Ok(value) => Ok(value.clamp(min, max).to_string()),
+			Err(_) => Err(Error::InvalidField(field.to_string())),
+		})
+	}
+
+	/// Replace each field of a column with its value in the lookup table. Fields that aren't in the table are replaced with `default`, or left unchanged if `default` is `None`.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	/// use std::collections::HashMap;
+	///
+	/// let table = HashMap::from([("NO".to_string(), "Norway".to_string())]);
+	/// let csv = Pipeline::from_str("Code\nNO\nTV\n")
+	///   .unwrap()
+	///   .map_col_lookup("Code", table, Some("Unknown"))
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "Code\nNorway\nUnknown\n");
+	/// ```
+	pub fn map_col_lookup(
+		self,
+		col: &str,
+		table: HashMap<String, String>,
+		default: Option<&str>,
+	) -> Self {
+		let default = default.map(String::from);
+		self.map_col(col, move |field| match (table.get(field), &default) {
+			(Some(value), _) => Ok(value.clone()),
+			(None, Some(default)) => Ok(default.clone()),
+			(None, None) => Ok(field.to_string()),
+		})
+	}
+
+	/// Like [`Pipeline::map_col_lookup`], but fields that aren't in the table result in an [`InvalidField`](Error::InvalidField) error.
+	pub fn map_col_lookup_strict(self, col: &str, table: HashMap<String, String>) -> Self {
+		self.map_col(col, move |field| match table.get(field) {
+			Some(value) => Ok(value.clone()),
+			None => Err(Error::InvalidField(field.to_string())),
+		})
+	}
+
+	/// Parse the fields of a column as `f64` and replace them with `value * factor + offset`, for example to convert units. The result is formatted with the specified number of decimals, or as short as possible if `decimals` is `None`. Fields that aren't numbers result in an [`InvalidField`](Error::InvalidField) error.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// // Celsius to Fahrenheit
+	/// let csv = Pipeline::from_str("Temperature\n-40\n21.5\n")
+	///   .unwrap()
+	///   .scale_col("Temperature", 1.8, 32.0, Some(1))
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "Temperature\n-40.0\n70.7\n");
+	/// ```
+	pub fn scale_col(self, col: &str, factor: f64, offset: f64, decimals: Option<usize>) -> Self {
+		self.map_col(col, move |field| {
+			let value: f64 = field
+				.parse()
+				.map_err(|_| Error::InvalidField(field.to_string()))?;
+			let scaled = value * factor + offset;
+			Ok(match decimals {
+				Some(decimals) => format!("{:.*}", decimals, scaled),
+				None => scaled.to_string(),
+			})
+		})
+	}
+
+	/// Parse the fields of a column as `f64`, replace them with the result of the closure, and write the result using the specified [`NumberFormat`]. Use [`NumberFormat::Preserve`] to avoid changing how the column is written, like dropping leading or trailing zeros. Fields that aren't numbers result in an [`InvalidField`](Error::InvalidField) error.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{NumberFormat, Pipeline};
+	///
+	/// let csv = Pipeline::from_str("Code,Price\n007,1.50\n010,2.00\n")
+	///   .unwrap()
+	///   .map_col_numeric("Code", |code| code + 1.0, NumberFormat::Preserve)
+	///   .map_col_numeric("Price", |price| price * 2.0, NumberFormat::Preserve)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "Code,Price\n008,3.00\n011,4.00\n");
+	/// ```
+	pub fn map_col_numeric<F>(self, col: &str, mut f: F, format: NumberFormat) -> Self
+	where
+		F: FnMut(f64) -> f64 + 'a,
+	{
+		self.map_col(col, move |field| {
+			let value: f64 = field
+				.parse()
+				.map_err(|_| Error::InvalidField(field.to_string()))?;
+			Ok(format.format(field, f(value)))
+		})
+	}
+
+	/// Filter rows using the provided closure.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .filter(|headers, row| {
+	///     let country = headers.get_field(&row, "Country").unwrap();
+	///     country == "Tuvalu"
+	///   })
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "ID,Country\n\
+	///     2,Tuvalu\n"
+	/// );
+	/// ```
+	pub fn filter<F>(mut self, get_row: F) -> Self
+	where
+		F: FnMut(&Headers, &Row) -> bool + 'a,
+	{
+		self.iterator = Box::new(Filter {
+			iterator: self.iterator,
+			f: get_row,
+			headers: self.headers.clone(),
+		});
+		self
+	}
+
+	/// Remove rows where every field is empty, like the `,,` rows some spreadsheet exports end with.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_str("A,B\n1,2\n,\n,3\n")
+	///   .unwrap()
+	///   .drop_empty_rows()
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "A,B\n1,2\n,3\n");
+	/// ```
+	pub fn drop_empty_rows(self) -> Self {
+		self.filter(|_headers, row| row.iter().any(|field| !field.is_empty()))
+	}
+
+	/// Remove rows where any field is empty.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_str("A,B\n1,2\n,\n,3\n")
+	///   .unwrap()
+	///   .drop_rows_with_any_empty()
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "A,B\n1,2\n");
+	/// ```
+	pub fn drop_rows_with_any_empty(self) -> Self {
+		self.filter(|_headers, row| row.iter().all(|field| !field.is_empty()))
+	}
+
+	/// Filter rows based on the field of the specified column, using the provided closure.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .filter_col("Country", |country| country == "Tuvalu")
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "ID,Country\n\
+	///     2,Tuvalu\n"
+	/// );
+	/// ```
+	pub fn filter_col<F>(mut self, name: &str, get_row: F) -> Self
+	where
+		F: FnMut(&str) -> bool + 'a,
+	{
+		self.iterator = Box::new(FilterCol {
+			name: name.to_string(),
+			iterator: self.iterator,
+			f: get_row,
+			source: self.source,
+			index: self.headers.get_index(name),
+		});
+		self
+	}
+
+	/// Keep only the rows where the field of the specified column is in `values`. This is faster than [`Pipeline::filter_col`] with a closure that searches a list, since each lookup is a hash lookup.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	/// use std::collections::HashSet;
+	///
+	/// let allowed: HashSet<String> = ["Tuvalu".to_string()].into_iter().collect();
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .retain_col_values("Country", allowed)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "ID,Country\n2,Tuvalu\n");
+	/// ```
+	pub fn retain_col_values(self, name: &str, values: HashSet<String>) -> Self {
+		self.filter_col(name, move |field| values.contains(field))
+	}
+
+	/// Remove the rows where the field of the specified column is in `values`, like a blocklist. This is the opposite of [`Pipeline::retain_col_values`].
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	/// use std::collections::HashSet;
+	///
+	/// let blocked: HashSet<String> = ["Tuvalu".to_string()].into_iter().collect();
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .reject_col_values("Country", blocked)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "ID,Country\n1,Norway\n");
+	/// ```
+	pub fn reject_col_values(self, name: &str, values: HashSet<String>) -> Self {
+		self.filter_col(name, move |field| !values.contains(field))
+	}
+
+	/// Keep only the rows whose key doesn't appear in `other`, where the key is the fields of the `on` columns. Every key of `other` is read into memory before the first row is returned.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let visited = Pipeline::from_str("Country\nNorway\n").unwrap();
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .anti_join(visited, vec!["Country"])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "ID,Country\n2,Tuvalu\n");
+	/// ```
+	pub fn anti_join(self, other: Pipeline<'a>, on: Vec<&str>) -> Self {
+		self.key_filter(other, on, false)
+	}
+
+	/// Keep only the rows whose key appears in `other`, where the key is the fields of the `on` columns. Unlike a join, no columns from `other` are added, and each row is kept at most once. Every key of `other` is read into memory before the first row is returned.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let visited = Pipeline::from_str("Country,Year\nNorway,2019\nNorway,2022\n").unwrap();
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .semi_join(visited, vec!["Country"])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "ID,Country\n1,Norway\n");
+	/// ```
+	pub fn semi_join(self, other: Pipeline<'a>, on: Vec<&str>) -> Self {
+		self.key_filter(other, on, true)
+	}
+
+	fn key_filter(mut self, other: Pipeline<'a>, on: Vec<&str>, keep_matches: bool) -> Self {
+		let key_columns = |headers: &Headers| -> Vec<(String, Option<usize>)> {
+			on.iter()
+				.map(|col| (col.to_string(), headers.get_index(col)))
+				.collect()
+		};
+		self.iterator = Box::new(KeyFilter {
+			iterator: self.iterator,
+			other_columns: key_columns(&other.headers),
+			other_source: other.source,
+			other: Some(other.build()),
+			columns: key_columns(&self.headers),
+			keys: HashSet::new(),
+			keep_matches,
+			source: self.source,
+		});
+		self
+	}
+
+	/// Remove rows whose key has already been seen, where the key is the fields of `key_cols`. If a row has the same key as an earlier row but different fields, it results in a [`ConflictingRows`](Error::ConflictingRows) error instead of being removed. This catches inconsistencies between merged sources that a plain deduplication would hide. The first row of every key is kept in memory.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Error, Pipeline};
+	///
+	/// let csv = Pipeline::from_pipelines(vec![
+	///   Pipeline::from_path("test/Countries.csv").unwrap(),
+	///   Pipeline::from_path("test/Countries.csv").unwrap(),
+	/// ])
+	///   .dedup_detecting_conflicts(vec!["ID"])
+	///   .collect_into_string()
+	///   .unwrap();
+	/// assert_eq!(csv, "ID,Country\n1,Norway\n2,Tuvalu\n");
+	///
+	/// let err = Pipeline::from_str("ID,Country\n1,Norway\n1,Sweden\n")
+	///   .unwrap()
+	///   .dedup_detecting_conflicts(vec!["ID"])
+	///   .collect_into_string()
+	///   .unwrap_err();
+	/// assert!(matches!(err.error, Error::ConflictingRows(..)));
+	/// assert!(err.row.is_some());
+	/// ```
+	pub fn dedup_detecting_conflicts(mut self, key_cols: Vec<&str>) -> Self {
+		self.iterator = Box::new(DedupConflicts {
+			iterator: self.iterator,
+			columns: key_cols
+				.into_iter()
+				.map(|col| (col.to_string(), self.headers.get_index(col)))
+				.collect(),
+			seen: HashMap::new(),
+			source: self.source,
+		});
+		self
+	}
+
+	/// Pick which columns to output, in the specified order. Panics if duplicate colums are specified.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/AB.csv")
+	///   .unwrap()
+	///   .select(vec!["B"])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "B\n2\n");
+	/// ```
+	pub fn select(self, columns: Vec<&str>) -> Self {
+		or_panic(self.try_select(columns))
+	}
+
+	/// Like [`Pipeline::select`], but returns a [`DuplicateColumn`](Error::DuplicateColumn) error instead of panicking if duplicate columns are specified.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Error, Pipeline};
+	///
+	/// let result = Pipeline::from_path("test/AB.csv")
+	///   .unwrap()
+	///   .try_select(vec!["A", "A"]);
+	///
+	/// assert!(matches!(result.err().unwrap().error, Error::DuplicateColumn(_)));
+	/// ```
+	pub fn try_select(mut self, columns: Vec<&str>) -> Result<Self, PlError> {
+		let new_headers = self.headers_from(columns.clone())?;
+		self.iterator = Box::new(Select {
+			iterator: self.iterator,
+			columns: columns
+				.into_iter()
+				.map(|col| (col.to_string(), self.headers.get_index(col)))
+				.collect(),
+			source: self.source,
+		});
+		self.headers = new_headers;
+		Ok(self)
+	}
+
+	/// Pick which columns to output, in the specified order. Unlike [`Pipeline::select`], columns that don't exist are output with empty fields instead of resulting in a [`MissingColumn`](Error::MissingColumn) error, which is useful for producing a fixed set of columns from inputs that vary. Panics if duplicate columns are specified.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/AB.csv")
+	///   .unwrap()
+	///   .select_lenient(vec!["B", "C"])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "B,C\n2,\n");
+	/// ```
+	pub fn select_lenient(self, columns: Vec<&str>) -> Self {
+		or_panic(self.try_select_lenient(columns))
+	}
+
+	/// Like [`Pipeline::select_lenient`], but returns a [`DuplicateColumn`](Error::DuplicateColumn) error instead of panicking if duplicate columns are specified.
+	pub fn try_select_lenient(mut self, columns: Vec<&str>) -> Result<Self, PlError> {
+		let new_headers = self.headers_from(columns.clone())?;
+		self.iterator = Box::new(Align {
+			iterator: self.iterator,
+			columns: columns
+				.into_iter()
+				.map(|col| (col.to_string(), self.headers.get_index(col)))
+				.collect(),
+			source: self.source,
+		});
+		self.headers = new_headers;
+		Ok(self)
+	}
+
+	/// Pick which columns to output by their zero-based index, in the specified order. If an index is out of range of the headers, the pipeline returns a [`MissingColumn`](Error::MissingColumn) error without reading any rows. Rows that are too short also result in that error. Panics if duplicate columns are specified.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/AB.csv")
+	///   .unwrap()
+	///   .select_indices(vec![1, 0])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "B,A\n2,1\n");
+	/// ```
+	pub fn select_indices(self, indices: Vec<usize>) -> Self {
+		if let Some(error) = self.missing_index(&indices) {
+			return self.fail_with(error);
+		}
+		or_panic(self.try_select_indices(indices))
+	}
+
+	/// Like [`Pipeline::select_indices`], but returns a [`DuplicateColumn`](Error::DuplicateColumn) error instead of panicking if duplicate columns are specified, and a [`MissingColumn`](Error::MissingColumn) error if an index is out of range.
+	pub fn try_select_indices(mut self, indices: Vec<usize>) -> Result<Self, PlError> {
+		if let Some(error) = self.missing_index(&indices) {
+			return Err(error.at_source(self.source));
+		}
+		let header_row = self.headers.get_row();
+		let names: Vec<&str> = indices.iter().filter_map(|i| header_row.get(*i)).collect();
+		let new_headers = self.headers_from(names)?;
+		self.iterator = Box::new(SelectIndices {
+			iterator: self.iterator,
+			indices,
+			source: self.source,
+		});
+		self.headers = new_headers;
+		Ok(self)
+	}
+
+	/// Swap the positions of two columns. If either column is missing, the pipeline returns a [`MissingColumn`](Error::MissingColumn) error without reading any rows.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .swap_cols("Country", "ID")
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Country,ID\n\
+	///     Norway,1\n\
+	///     Tuvalu,2\n"
+	/// );
+	/// ```
+	pub fn swap_cols(self, a: &str, b: &str) -> Self {
+		let (index_a, index_b) = match (self.headers.get_index(a), self.headers.get_index(b)) {
+			(Some(index_a), Some(index_b)) => (index_a, index_b),
+			(None, _) => return self.fail_with(Error::MissingColumn(a.to_string())),
+			(_, None) => return self.fail_with(Error::MissingColumn(b.to_string())),
+		};
+		let mut indices: Vec<usize> = (0..self.headers.get_row().len()).collect();
+		indices.swap(index_a, index_b);
+		self.select_indices(indices)
+	}
+
+	/// Panics if a new name already exists
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/AB.csv")
+	///   .unwrap()
+	///   .rename_col("A", "X")
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "X,B\n1,2\n");
+	/// ```
+	pub fn rename_col(mut self, from: &str, to: &str) -> Self {
+		match self.headers.rename(from, to) {
+			Ok(()) => self,
+			Err(e) => panic!("Error renaming column in source {}: {}", self.source, e),
+		}
+	}
+
+	/// Like [`Pipeline::rename_col`], but returns an error instead of panicking: [`MissingColumn`](Error::MissingColumn) if `from` doesn't exist, or [`DuplicateColumn`](Error::DuplicateColumn) if `to` already exists.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Error, Pipeline};
+	///
+	/// let result = Pipeline::from_path("test/AB.csv")
+	///   .unwrap()
+	///   .try_rename_col("A", "B");
+	///
+	/// assert!(matches!(result.err().unwrap().error, Error::DuplicateColumn(_)));
+	/// ```
+	pub fn try_rename_col(mut self, from: &str, to: &str) -> Result<Self, PlError> {
+		match self.headers.rename(from, to) {
+			Ok(()) => Ok(self),
+			Err(RenameError::MissingColumn) => {
+				Err(Error::MissingColumn(from.to_string()).at_source(self.source))
+			}
+			Err(RenameError::DuplicateColumn(_)) => {
+				Err(Error::DuplicateColumn(to.to_string()).at_source(self.source))
+			}
+		}
+	}
+
+	/// Panics if a new name already exists
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/AB.csv")
+	///   .unwrap()
+	///   .rename_cols(|i, name| {
+	///     match name {
+	///       "A" => "X",
+	///       name => name,
+	///     }
+	///   })
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "X,B\n1,2\n");
+	/// ```
+	pub fn rename_cols<R>(self, get_name: R) -> Self
+	where
+		R: FnMut(usize, &str) -> &str,
+	{
+		let result = self.try_rename_cols(get_name);
+		or_panic_duplicate(result, |_| "New column name already exists".to_string())
+	}
+
+	/// Like [`Pipeline::rename_cols`], but returns a [`DuplicateColumn`](Error::DuplicateColumn) error instead of panicking if a new name already exists.
+	pub fn try_rename_cols<R>(mut self, mut get_name: R) -> Result<Self, PlError>
+	where
+		R: FnMut(usize, &str) -> &str,
+	{
+		let mut new_headers = Headers::new();
+		for (i, name) in self.headers.into_iter().enumerate().borrow_mut() {
+			let new_name = get_name(i, name);
+			if !new_headers.push_field(new_name) {
+				let error = Error::DuplicateColumn(new_name.to_string());
+				return Err(error.at_source(self.source));
+			}
+		}
+		self.headers = new_headers;
+		Ok(self)
+	}
+
+	/// Replace every column name. If the number of names doesn't match the number of columns, the pipeline returns a [`MismatchedHeaders`](Error::MismatchedHeaders) error without reading any rows. Panics if a name is specified twice.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/AB.csv")
+	///   .unwrap()
+	///   .set_headers(vec!["X", "Y"])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "X,Y\n1,2\n");
+	/// ```
+	pub fn set_headers(self, names: Vec<&str>) -> Self {
+		if let Some(error) = self.mismatched_headers(&names) {
+			return self.fail_with(error);
+		}
+		let result = self.try_set_headers(names);
+		or_panic_duplicate(result, |name| {
+			format!("Column name specified twice: {}", name)
+		})
+	}
+
+	/// Like [`Pipeline::set_headers`], but returns a [`DuplicateColumn`](Error::DuplicateColumn) error instead of panicking if a name is specified twice, and a [`MismatchedHeaders`](Error::MismatchedHeaders) error if the number of names is wrong.
+	pub fn try_set_headers(mut self, names: Vec<&str>) -> Result<Self, PlError> {
+		if let Some(error) = self.mismatched_headers(&names) {
+			return Err(error.at_source(self.source));
+		}
+		self.headers = self.headers_from(names)?;
+		Ok(self)
+	}
+
+	/// Group and reduce rows into the provided format. If the transform results in duplicate column names, the pipeline returns a [`DuplicateColumn`](Error::DuplicateColumn) error. If any of a transformer's [source columns](Transform::source_cols) don't exist, the pipeline returns a [`MissingColumn`](Error::MissingColumn) error before reading any rows.
+	///
+	/// Every group is kept in memory until all rows have been read. If the rows are sorted by the grouping columns, for example using [`sort_by`](Pipeline::sort_by), use [`transform_sorted`](Pipeline::transform_sorted) instead to return each group as soon as it's complete.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/AB.csv")
+	///   .unwrap()
+	///   .rename_cols(|i, name| {
+	///     match name {
+	///       "A" => "X",
+	///       name => name,
+	///     }
+	///   })
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "X,B\n1,2\n");
+	/// ```
+	pub fn transform_into<T>(self, get_transformers: T) -> Self
+	where
+		T: FnMut() -> Vec<Box<dyn Transform>> + 'a,
+	{
+		self.transform_into_with(get_transformers, TransformOptions::new())
+	}
+
+	/// Like [`transform_into`](Pipeline::transform_into), but using the specified [`TransformOptions`].
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Error, Pipeline, TransformOptions, Transformer};
+	///
+	/// let err = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .transform_into_with(
+	///     || vec![Transformer::new("Country").keep_unique()],
+	///     TransformOptions::new().max_groups(1),
+	///   )
+	///   .collect_into_string()
+	///   .unwrap_err();
+	///
+	/// assert!(matches!(err.error, Error::TooManyGroups(1)));
+	/// ```
+	pub fn transform_into_with<T>(self, mut get_transformers: T, options: TransformOptions) -> Self
+	where
+		T: FnMut() -> Vec<Box<dyn Transform>> + 'a,
+	{
+		let hashers = get_transformers();
+		let names: Vec<_> = hashers.iter().map(|hasher| hasher.name()).collect();
+		if let Some(col) = self.missing_source_col(&hashers) {
+			return self.fail_with(Error::MissingColumn(col));
+		}
+		Pipeline {
+			headers: Headers::new(),
+			source: self.source,
+			file: self.file,
+			source_label: None,
+			iterator: Box::new(TransformInto {
+				iterator: self.iterator,
+				groups: LinkedHashMap::new(),
+				hashers: get_transformers(),
+				get_transformers,
+				max_groups: options.max_groups,
+				hasher: options.hasher,
+				spill: options.spill.map(|(dir, max)| Spill::new(dir, max)),
+				source: self.source,
+				headers: self.headers,
+			}),
+		}
+		.with_output_headers(names)
+	}
+
+	/// Group and reduce rows that are already sorted by the key columns. Each group is returned as soon as a row with a different key is found, so only one group is kept in memory, unlike [`transform_into`](Pipeline::transform_into). If the input isn't sorted, rows with the same key that aren't next to each other become separate groups.
+	///
+	/// The output has the key columns followed by the transformer columns. If this results in duplicate column names, the pipeline returns a [`DuplicateColumn`](Error::DuplicateColumn) error.
+	///
+	/// This isn't detected automatically, so sorting followed by [`transform_into`](Pipeline::transform_into) still keeps every group in memory. To use the streaming grouping for unsorted input, sort by the key columns first:
+	///
+	/// ```
+	/// use csv_pipeline::{Order, Pipeline, SortKey, Transformer};
+	///
+	/// let csv = Pipeline::from_str("Person,Score\nB,3\nA,1\nB,4\nA,8\n")
+	///   .unwrap()
+	///   .sort_by("Person", Order::Asc, SortKey::Text)
+	///   .transform_sorted(vec!["Person"], || {
+	///     vec![Transformer::new("Total score").from_col("Score").sum(0)]
+	///   })
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "Person,Total score\nA,9\nB,7\n");
+	/// ```
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Transformer};
+	///
+	/// let source = "\
+	///   Person,Score\n\
+	///   A,1\n\
+	///   A,8\n\
+	///   B,3\n\
+	///   B,4\n";
+	/// let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+	///   .unwrap()
+	///   .transform_sorted(vec!["Person"], || {
+	///     vec![Transformer::new("Total score").from_col("Score").sum(0)]
+	///   })
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Person,Total score\n\
+	///     A,9\n\
+	///     B,7\n"
+	/// );
+	/// ```
+	pub fn transform_sorted<T>(self, key_cols: Vec<&str>, mut get_transformers: T) -> Self
+	where
+		T: FnMut() -> Vec<Box<dyn Transform>> + 'a,
+	{
+		let mut names: Vec<String> = key_cols.iter().map(|col| col.to_string()).collect();
+		let transformers = get_transformers();
+		names.extend(transformers.iter().map(|t| t.name()));
+		if let Some(col) = self.missing_source_col(&transformers) {
+			return self.fail_with(Error::MissingColumn(col));
+		}
+		let key_cols = key_cols
+			.into_iter()
+			.map(|col| (col.to_string(), self.headers.get_index(col)))
+			.collect();
+		Pipeline {
+			headers: Headers::new(),
+			source: self.source,
+			file: self.file,
+			source_label: None,
+			iterator: Box::new(TransformSorted {
+				iterator: self.iterator,
+				key_cols,
+				get_transformers,
+				current: None,
+				pending_error: None,
+				source: self.source,
+				headers: self.headers,
+			}),
+		}
+		.with_output_headers(names)
+	}
+
+	/// Reduce all rows into a single summary row. Unlike [`transform_into`](Pipeline::transform_into), rows are not grouped, and exactly one row is returned even if there are no input rows. If the transform results in duplicate column names, the pipeline returns a [`DuplicateColumn`](Error::DuplicateColumn) error.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Transformer};
+	///
+	/// let source = "\
+	///   Person,Score\n\
+	///   A,1\n\
+	///   A,8\n\
+	///   B,3\n";
+	/// let reader = csv::Reader::from_reader(source.as_bytes());
+	/// let csv = Pipeline::from_reader(reader)
+	///   .unwrap()
+	///   .aggregate(vec![
+	///     Transformer::new("Total score").from_col("Score").sum(0),
+	///     Transformer::new("Rows").count(),
+	///   ])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "Total score,Rows\n12,3\n");
+	/// ```
+	pub fn aggregate(self, transformers: Vec<Box<dyn Transform + 'a>>) -> Self {
+		let names: Vec<_> = transformers.iter().map(|t| t.name()).collect();
+		if let Some(col) = self.missing_source_col(&transformers) {
+			return self.fail_with(Error::MissingColumn(col));
+		}
+		Pipeline {
+			headers: Headers::new(),
+			source: self.source,
+			file: self.file,
+			source_label: None,
+			iterator: Box::new(Aggregate {
+				iterator: self.iterator,
+				transformers: Some(transformers),
+				source: self.source,
+				headers: self.headers,
+			}),
+		}
+		.with_output_headers(names)
+	}
+
+	/// Adds a column with each row's value from `col` as a percentage of the column's total. Values are parsed as `f64`. If the total is zero, the new fields are empty. If `into` already exists, the pipeline returns a [`DuplicateColumn`](Error::DuplicateColumn) error.
+	///
+	/// Every row is kept in memory, since the total is needed before any row can be returned.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let source = "\
+	///   Region,Sales\n\
+	///   North,30\n\
+	///   South,10\n";
+	/// let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+	///   .unwrap()
+	///   .add_pct_of_total("Sales", "Share")
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Region,Sales,Share\n\
+	///     North,30,75\n\
+	///     South,10,25\n"
+	/// );
+	/// ```
+	pub fn add_pct_of_total(mut self, col: &str, into: &str) -> Self {
+		let index = self.headers.get_index(col);
+		let col = col.to_string();
+		if !self.headers.push_field(into) {
+			return self.fail_with(Error::DuplicateColumn(into.to_string()));
+		}
+		let pct_of_total = move |rows: Vec<Row>| {
+			let index = index.ok_or_else(|| Error::MissingColumn(col.clone()))?;
+			let values = parse_col(&rows, index, &col)?;
+			let total: f64 = values.iter().sum();
+			let rows = rows.into_iter().zip(values).map(|(mut row, value)| {
+				match total == 0.0 {
+					true => row.push_field(""),
+					false => row.push_field(&(value / total * 100.0).to_string()),
+				}
+				row
+			});
+			Ok(rows.collect())
+		};
+		self.source_label = None;
+		self.iterator = Box::new(Buffered::new(self.iterator, pct_of_total, self.source));
+		self
+	}
+
+	/// Adds a column with the rank of each row's value from `col`, while keeping the original row order. Values are parsed as `f64`. Equal values share a rank, and the next rank is skipped (1, 1, 3). See [`dense_rank_col`](Pipeline::dense_rank_col) for ranks without gaps. If `into` already exists, the pipeline returns a [`DuplicateColumn`](Error::DuplicateColumn) error.
+	///
+	/// Every row is kept in memory.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Order, Pipeline};
+	///
+	/// let source = "\
+	///   Player,Score\n\
+	///   A,5\n\
+	///   B,9\n\
+	///   C,5\n\
+	///   D,1\n";
+	/// let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+	///   .unwrap()
+	///   .rank_col("Score", "Rank", Order::Desc)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Player,Score,Rank\n\
+	///     A,5,2\n\
+	///     B,9,1\n\
+	///     C,5,2\n\
+	///     D,1,4\n"
+	/// );
+	/// ```
+	pub fn rank_col(self, col: &str, into: &str, order: Order) -> Self {
+		self.add_rank_col(col, into, order, false)
+	}
+
+	/// Like [`rank_col`](Pipeline::rank_col), but ranks have no gaps (1, 1, 2).
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Order, Pipeline};
+	///
+	/// let source = "\
+	///   Player,Score\n\
+	///   A,5\n\
+	///   B,9\n\
+	///   C,5\n\
+	///   D,1\n";
+	/// let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+	///   .unwrap()
+	///   .dense_rank_col("Score", "Rank", Order::Desc)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Player,Score,Rank\n\
+	///     A,5,2\n\
+	///     B,9,1\n\
+	///     C,5,2\n\
+	///     D,1,3\n"
+	/// );
+	/// ```
+	pub fn dense_rank_col(self, col: &str, into: &str, order: Order) -> Self {
+		self.add_rank_col(col, into, order, true)
+	}
+
+	/// Adds a column with the rank of each row's value from `value_col` within its partition, like SQL's `RANK() OVER (PARTITION BY ...)`. Rows with equal fields in all of the `partition_cols` belong to the same partition. Ranking works like [`rank_col`](Pipeline::rank_col), and the original row order is kept. If `into` already exists, the pipeline returns a [`DuplicateColumn`](Error::DuplicateColumn) error.
+	///
+	/// Every row is kept in memory, along with the partition key of each row.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Order, Pipeline};
+	///
+	/// let source = "\
+	///   Category,Product,Sales\n\
+	///   Fruit,Apple,5\n\
+	///   Tools,Hammer,2\n\
+	///   Fruit,Pear,8\n\
+	///   Tools,Saw,3\n";
+	/// let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+	///   .unwrap()
+	///   .rank_within(vec!["Category"], "Sales", "Rank", Order::Desc)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Category,Product,Sales,Rank\n\
+	///     Fruit,Apple,5,2\n\
+	///     Tools,Hammer,2,2\n\
+	///     Fruit,Pear,8,1\n\
+	///     Tools,Saw,3,1\n"
+	/// );
+	/// ```
+	pub fn rank_within(
+		mut self,
+		partition_cols: Vec<&str>,
+		value_col: &str,
+		into: &str,
+		order: Order,
+	) -> Self {
+		let headers = self.headers.clone();
+		let partition_cols: Vec<String> = partition_cols.into_iter().map(String::from).collect();
+		let value_col = value_col.to_string();
+		if !self.headers.push_field(into) {
+			return self.fail_with(Error::DuplicateColumn(into.to_string()));
+		}
+		let rank_rows = move |rows: Vec<Row>| {
+			let mut partitions: LinkedHashMap<Vec<&str>, Vec<usize>> = LinkedHashMap::new();
+			for (i, row) in rows.iter().enumerate() {
+				let mut key = Vec::with_capacity(partition_cols.len());
+				for col in &partition_cols {
+					let field = headers
+						.get_field(row, col)
+						.ok_or_else(|| Error::MissingColumn(col.clone()))?;
+					key.push(field);
+				}
+				partitions.entry(key).or_default().push(i);
+			}
+			let index = headers
+				.get_index(&value_col)
+				.ok_or_else(|| Error::MissingColumn(value_col.clone()))?;
+			let values = parse_col(&rows, index, &value_col)?;
+			let mut ranks = vec![0; rows.len()];
+			for (_, indexes) in partitions {
+				let partition_values: Vec<f64> = indexes.iter().map(|i| values[*i]).collect();
+				for (i, rank) in indexes.iter().zip(rank(&partition_values, order, false)) {
+					ranks[*i] = rank;
+				}
+			}
+			let rows = rows.into_iter().zip(ranks).map(|(mut row, rank)| {
+				row.push_field(&rank.to_string());
+				row
+			});
+			Ok(rows.collect())
+		};
+		self.source_label = None;
+		self.iterator = Box::new(Buffered::new(self.iterator, rank_rows, self.source));
+		self
+	}
+
+	fn add_rank_col(mut self, col: &str, into: &str, order: Order, dense: bool) -> Self {
+		let index = self.headers.get_index(col);
+		let col = col.to_string();
+		if !self.headers.push_field(into) {
+			return self.fail_with(Error::DuplicateColumn(into.to_string()));
+		}
+		let rank_rows = move |rows: Vec<Row>| {
+			let index = index.ok_or_else(|| Error::MissingColumn(col.clone()))?;
+			let values = parse_col(&rows, index, &col)?;
+			let ranks = rank(&values, order, dense);
+			let rows = rows.into_iter().zip(ranks).map(|(mut row, rank)| {
+				row.push_field(&rank.to_string());
+				row
+			});
+			Ok(rows.collect())
+		};
+		self.source_label = None;
+		self.iterator = Box::new(Buffered::new(self.iterator, rank_rows, self.source));
+		self
+	}
+
+	/// Adds a column with the value of `col` from `offset` rows earlier. The new field is empty for the first `offset` rows. If `into` already exists, the pipeline returns a [`DuplicateColumn`](Error::DuplicateColumn) error.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let source = "\
+	///   Event,Time\n\
+	///   a,1\n\
+	///   b,4\n\
+	///   c,9\n";
+	/// let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+	///   .unwrap()
+	///   .lag_col("Time", "Previous time", 1)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Event,Time,Previous time\n\
+	///     a,1,\n\
+	///     b,4,1\n\
+	///     c,9,4\n"
+	/// );
+	/// ```
+	pub fn lag_col(mut self, col: &str, into: &str, offset: usize) -> Self {
+		let index = self.headers.get_index(col);
+		if !self.headers.push_field(into) {
+			return self.fail_with(Error::DuplicateColumn(into.to_string()));
+		}
+		self.iterator = Box::new(Lag {
+			iterator: self.iterator,
+			name: col.to_string(),
+			index,
+			offset,
+			previous: VecDeque::with_capacity(offset + 1),
+			source: self.source,
+		});
+		self
+	}
+
+	/// Adds a column with the highest value of `col` seen so far. Fields are parsed as `f64`, and the new field keeps the original formatting of the highest field. If `into` already exists, the pipeline returns a [`DuplicateColumn`](Error::DuplicateColumn) error.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let source = "\
+	///   Day,Level\n\
+	///   1,3\n\
+	///   2,7\n\
+	///   3,5\n";
+	/// let csv = Pipeline::from_str(source)
+	///   .unwrap()
+	///   .running_max("Level", "High water")
+	///   .running_min("Level", "Low water")
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Day,Level,High water,Low water\n\
+	///     1,3,3,3\n\
+	///     2,7,7,3\n\
+	///     3,5,7,3\n"
+	/// );
+	/// ```
+	pub fn running_max(self, col: &str, into: &str) -> Self {
+		self.add_running_extreme(col, into, Ordering::Greater)
+	}
+
+	/// Adds a column with the lowest value of `col` seen so far. Works like [`Pipeline::running_max`].
+	pub fn running_min(self, col: &str, into: &str) -> Self {
+		self.add_running_extreme(col, into, Ordering::Less)
+	}
+
+	fn add_running_extreme(mut self, col: &str, into: &str, keep: Ordering) -> Self {
+		let index = self.headers.get_index(col);
+		if !self.headers.push_field(into) {
+			return self.fail_with(Error::DuplicateColumn(into.to_string()));
+		}
+		self.iterator = Box::new(RunningExtreme {
+			iterator: self.iterator,
+			name: col.to_string(),
+			index,
+			keep,
+			current: None,
+			source: self.source,
+		});
+		self
+	}
+
+	/// Adds a column with the mean of the last `window` values of `col`, including the current row, for smoothing noisy data. Fields are parsed as `f64`, and fields that aren't numbers result in an [`InvalidField`](Error::InvalidField) error. For the first `window - 1` rows, the window isn't full yet: if `partial` is true, the mean of the values so far is used, otherwise the new field is empty. If `into` already exists, the pipeline returns a [`DuplicateColumn`](Error::DuplicateColumn) error.
+	///
+	/// Only the last `window` values are kept in memory. Panics if `window` is 0.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let source = "\
+	///   Minute,Reading\n\
+	///   1,2\n\
+	///   2,4\n\
+	///   3,9\n";
+	/// let csv = Pipeline::from_str(source)
+	///   .unwrap()
+	///   .moving_average("Reading", 2, "Smoothed", false)
+	///   .moving_average("Reading", 2, "Partial", true)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Minute,Reading,Smoothed,Partial\n\
+	///     1,2,,2\n\
+	///     2,4,3,3\n\
+	///     3,9,6.5,6.5\n"
+	/// );
+	/// ```
+	pub fn moving_average(mut self, col: &str, window: usize, into: &str, partial: bool) -> Self {
+		assert!(window > 0, "Window must be greater than 0");
+		let index = self.headers.get_index(col);
+		if !self.headers.push_field(into) {
+			return self.fail_with(Error::DuplicateColumn(into.to_string()));
+		}
+		self.iterator = Box::new(MovingAverage {
+			iterator: self.iterator,
+			name: col.to_string(),
+			index,
+			window,
+			partial,
+			values: VecDeque::with_capacity(window),
+			source: self.source,
+		});
+		self
+	}
+
+	/// Fill empty fields of a numeric column by linear interpolation between the nearest non-empty fields before and after them. Fields are parsed as `f64`, and fields that aren't numbers result in an [`InvalidField`](Error::InvalidField) error. Empty fields at the start or end don't have a value on both sides: if `fill_edges` is true, they get the nearest value, otherwise they're left empty.
+	///
+	/// Rows with empty fields are kept in memory until the next non-empty field is found.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let source = "\
+	///   Day,Level\n\
+	///   1,\n\
+	///   2,1\n\
+	///   3,\n\
+	///   4,\n\
+	///   5,4\n\
+	///   6,\n";
+	/// let csv = Pipeline::from_str(source)
+	///   .unwrap()
+	///   .interpolate_col("Level", true)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Day,Level\n\
+	///     1,1\n\
+	///     2,1\n\
+	///     3,2\n\
+	///     4,3\n\
+	///     5,4\n\
+	///     6,4\n"
+	/// );
+	/// ```
+	pub fn interpolate_col(mut self, col: &str, fill_edges: bool) -> Self {
+		self.source_label = None;
+		self.iterator = Box::new(FillGaps {
+			iterator: self.iterator,
+			name: col.to_string(),
+			index: self.headers.get_index(col),
+			fill: GapFill::Interpolate { fill_edges },
+			previous: None,
+			gap: VecDeque::new(),
+			ready: VecDeque::new(),
+			source: self.source,
+		});
+		self
+	}
+
+	/// Fill empty fields of a column with the last non-empty field before them, for columns where a value applies until it changes. Empty fields before the first non-empty field are left empty.
+	///
+	/// This only keeps the last non-empty field in memory, unlike [`back_fill`](Pipeline::back_fill).
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_str("Category,Item\n,x\nFruit,Apple\n,Pear\nVegetable,Leek\n")
+	///   .unwrap()
+	///   .forward_fill("Category")
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Category,Item\n\
+	///     ,x\n\
+	///     Fruit,Apple\n\
+	///     Fruit,Pear\n\
+	///     Vegetable,Leek\n"
+	/// );
+	/// ```
+	pub fn forward_fill(self, col: &str) -> Self {
+		let mut last = String::new();
+		self.map_col(col, move |field| {
+			if !field.is_empty() {
+				last = field.to_string();
+			}
+			Ok(last.clone())
+		})
+	}
+
+	/// Fill empty fields of a column with the next non-empty field after them. Empty fields after the last non-empty field are left empty.
+	///
+	/// Rows with empty fields are kept in memory until the next non-empty field is found, unlike [`forward_fill`](Pipeline::forward_fill), which doesn't need to look ahead.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_str("Item,Total\nApple,\nPear,\nSum,5\nLeek,\n")
+	///   .unwrap()
+	///   .back_fill("Total")
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Item,Total\n\
+	///     Apple,5\n\
+	///     Pear,5\n\
+	///     Sum,5\n\
+	///     Leek,\n"
+	/// );
+	/// ```
+	pub fn back_fill(mut self, col: &str) -> Self {
+		self.source_label = None;
+		self.iterator = Box::new(FillGaps {
+			iterator: self.iterator,
+			name: col.to_string(),
+			index: self.headers.get_index(col),
+			fill: GapFill::BackFill,
+			previous: None,
+			gap: VecDeque::new(),
+			ready: VecDeque::new(),
+			source: self.source,
+		});
+		self
+	}
+
+	/// Adds a column with the value of `col` from `offset` rows later. The new field is empty for the last `offset` rows. If `into` already exists, the pipeline returns a [`DuplicateColumn`](Error::DuplicateColumn) error.
+	///
+	/// Up to `offset` rows are read ahead and kept in memory. Errors are returned in their original position, and aren't counted as rows.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let source = "\
+	///   Event,Time\n\
+	///   a,1\n\
+	///   b,4\n\
+	///   c,9\n";
+	/// let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+	///   .unwrap()
+	///   .lead_col("Time", "Next time", 1)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Event,Time,Next time\n\
+	///     a,1,4\n\
+	///     b,4,9\n\
+	///     c,9,\n"
+	/// );
+	/// ```
+	pub fn lead_col(mut self, col: &str, into: &str, offset: usize) -> Self {
+		let index = self.headers.get_index(col);
+		if !self.headers.push_field(into) {
+			return self.fail_with(Error::DuplicateColumn(into.to_string()));
+		}
+		self.source_label = None;
+		self.iterator = Box::new(Lead {
+			iterator: self.iterator,
+			name: col.to_string(),
+			index,
+			offset,
+			ahead: VecDeque::with_capacity(offset + 1),
+			done: false,
+			source: self.source,
+		});
+		self
+	}
+
+	/// Sort the rows by a column. The sort is stable, so rows with equal fields keep their order.
+	///
+	/// Every row is kept in memory.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Order, Pipeline, SortKey};
+	///
+	/// let source = "\
+	///   File\n\
+	///   file10\n\
+	///   file2\n\
+	///   file1\n";
+	/// let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+	///   .unwrap()
+	///   .sort_by("File", Order::Asc, SortKey::Natural)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "File\n\
+	///     file1\n\
+	///     file2\n\
+	///     file10\n"
+	/// );
+	/// ```
+	pub fn sort_by(self, col: &str, order: Order, key: SortKey) -> Self {
+		self.sort_by_keys(vec![(col, order, key)])
+	}
+
+	/// Sort the rows by multiple columns. Rows are sorted by the first column, and ties are broken by the following columns. The sort is stable, so rows with equal fields keep their order.
+	///
+	/// Every row is kept in memory.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Order, Pipeline, SortKey};
+	///
+	/// let source = "\
+	///   Region,Sales\n\
+	///   South,5\n\
+	///   North,3\n\
+	///   South,20\n\
+	///   North,7\n";
+	/// let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+	///   .unwrap()
+	///   .sort_by_keys(vec![
+	///     ("Region", Order::Asc, SortKey::Text),
+	///     ("Sales", Order::Desc, SortKey::Numeric),
+	///   ])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "Region,Sales\n\
+	///     North,7\n\
+	///     North,3\n\
+	///     South,20\n\
+	///     South,5\n"
+	/// );
+	/// ```
+	pub fn sort_by_keys(mut self, keys: Vec<(&str, Order, SortKey)>) -> Self {
+		let keys: Vec<_> = keys
+			.into_iter()
+			.map(|(col, order, key)| (col.to_string(), self.headers.get_index(col), order, key))
+			.collect();
+		let sort_rows = move |rows: Vec<Row>| {
+			let mut keyed_rows = Vec::with_capacity(rows.len());
+			for row in rows {
+				let mut values = Vec::with_capacity(keys.len());
+				for (col, index, _, key) in &keys {
+					let field = index
+						.and_then(|index| row.get(index))
+						.ok_or_else(|| Error::MissingColumn(col.clone()))?;
+					values.push(SortValue::new(*key, field)?);
+				}
+				keyed_rows.push((values, row));
+			}
+			keyed_rows.sort_by(|(a, _), (b, _)| {
+				let mut ordering = std::cmp::Ordering::Equal;
+				for (((_, _, order, _), a), b) in keys.iter().zip(a).zip(b) {
+					ordering = ordering.then_with(|| order.apply(a.compare(b)));
+				}
+				ordering
+			});
+			Ok(keyed_rows.into_iter().map(|(_, row)| row).collect())
+		};
+		self.source_label = None;
+		self.iterator = Box::new(Buffered::new(self.iterator, sort_rows, self.source));
+		self
+	}
+
+	/// Shuffle the rows randomly. The same seed always results in the same order, as long as the version of [`rand`](https://crates.io/crates/rand) stays the same.
+	///
+	/// Every row is kept in memory. Requires the `rand` feature.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let shuffle = |seed| {
+	///   Pipeline::from_rows((0..10).map(|i| csv_pipeline::Row::from(vec![i.to_string()])))
+	///     .unwrap()
+	///     .shuffle(seed)
+	///     .collect_into_string()
+	///     .unwrap()
+	/// };
+	/// assert_eq!(shuffle(1), shuffle(1));
+	/// assert_ne!(shuffle(1), shuffle(2));
+	/// ```
+	#[cfg(feature = "rand")]
+	pub fn shuffle(mut self, seed: u64) -> Self {
+		use rand::rngs::StdRng;
+		use rand::seq::SliceRandom;
+		use rand::SeedableRng;
+
+		let shuffle_rows = move |mut rows: Vec<Row>| {
+			rows.shuffle(&mut StdRng::seed_from_u64(seed));
+			Ok(rows)
+		};
+		self.source_label = None;
+		self.iterator = Box::new(Buffered::new(self.iterator, shuffle_rows, self.source));
+		self
+	}
+
+	/// Adds a column that randomly assigns each row to `"train"` or `"test"`, for splitting data into training and test sets. Each row has a `frac` probability of being `"train"`. The same seed always results in the same assignments, as long as the version of [`rand`](https://crates.io/crates/rand) stays the same. Panics if `frac` is outside `[0, 1]`.
+	///
+	/// Requires the `rand` feature.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .add_split_col("Split", 1.0, 0)
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "ID,Country,Split\n\
+	///     1,Norway,train\n\
+	///     2,Tuvalu,train\n"
+	/// );
+	/// ```
+	#[cfg(feature = "rand")]
+	pub fn add_split_col(self, name: &str, frac: f64, seed: u64) -> Self {
+		use rand::rngs::StdRng;
+		use rand::{Rng, SeedableRng};
+
+		assert!(
+			(0.0..=1.0).contains(&frac),
+			"Fraction must be between 0 and 1, got {}",
+			frac
+		);
+		let mut rng = StdRng::seed_from_u64(seed);
+		self.add_col(name, move |_headers, _row| match rng.gen::<f64>() < frac {
+			true => Ok("train".to_string()),
+			false => Ok("test".to_string()),
+		})
+	}
+
+	/// Adds a column with a bucket number from `0` to `buckets - 1`, computed from a hash of the field in `col`. Equal fields always get the same bucket, including across program runs and platforms, which makes it useful for sharding records consistently. If `into` already exists, the pipeline returns a [`DuplicateColumn`](Error::DuplicateColumn) error. Panics if `buckets` is 0.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .bucket_col("Country", 4, "Shard")
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(
+	///   csv,
+	///   "ID,Country,Shard\n\
+	///     1,Norway,3\n\
+	///     2,Tuvalu,0\n"
+	/// );
+	/// ```
+	pub fn bucket_col(self, col: &str, buckets: u64, into: &str) -> Self {
+		assert!(buckets > 0, "Number of buckets must be greater than 0");
+		let index = self.headers.get_index(col);
+		let col = col.to_string();
+		self.add_col(into, move |_headers, row| {
+			let field = index
+				.and_then(|index| row.get(index))
+				.ok_or_else(|| Error::MissingColumn(col.clone()))?;
+			Ok((fnv1a(field.as_bytes()) % buckets).to_string())
+		})
+	}
+
+	/// Do your own validation on each row.
+	pub fn validate<F>(mut self, f: F) -> Self
+	where
+		F: FnMut(&Headers, &Row) -> Result<(), Error> + 'a,
+	{
+		self.iterator = Box::new(Validate {
+			iterator: self.iterator,
+			f,
+			source: self.source,
+			headers: self.headers.clone(),
+		});
+		self
+	}
+
+	/// Do your own validation on the fields in a column.
+	pub fn validate_col<F>(mut self, name: &str, f: F) -> Self
+	where
+		F: FnMut(&str) -> Result<(), Error> + 'a,
+	{
+		self.iterator = Box::new(ValidateCol {
+			name: name.to_string(),
+			iterator: self.iterator,
+			f,
+			source: self.source,
+			index: self.headers.get_index(name),
+		});
+		self
+	}
+
+	/// Set the headers of a step that outputs new columns. If a name is duplicated, the pipeline returns a single [`DuplicateColumn`](Error::DuplicateColumn) error instead of any rows.
+	fn with_output_headers(mut self, names: Vec<String>) -> Self {
+		match Headers::from_row(Row::from(names)) {
+			Ok(headers) => self.headers = headers,
+			Err(name) => return self.fail_with(Error::DuplicateColumn(name)),
+		}
+		self
+	}
+
+	/// Create headers from column names, returning a [`DuplicateColumn`](Error::DuplicateColumn) error if a name appears twice
+	fn headers_from(&self, names: Vec<&str>) -> Result<Headers, PlError> {
+		Headers::from_row(Row::from(names))
+			.map_err(|name| Error::DuplicateColumn(name).at_source(self.source))
+	}
+
+	/// A [`MissingColumn`](Error::MissingColumn) error for the first index that is out of range of the headers
+	fn missing_index(&self, indices: &[usize]) -> Option<Error> {
+		let len = self.headers.get_row().len();
+		let index = indices.iter().find(|&&index| index >= len)?;
+		let name = format!("at index {} of {} columns", index, len);
+		Some(Error::MissingColumn(name))
+	}
+
+	/// A [`MismatchedHeaders`](Error::MismatchedHeaders) error if the number of names doesn't match the number of columns
+	fn mismatched_headers(&self, names: &[&str]) -> Option<Error> {
+		let row = self.headers.get_row();
+		match row.len() == names.len() {
+			true => None,
+			false => Some(Error::MismatchedHeaders(
+				row.clone(),
+				Row::from(names.to_vec()),
+			)),
+		}
+	}
+
+	/// Make the pipeline return a single error instead of any rows, for problems found while building it
+	fn fail_with(mut self, error: Error) -> Self {
+		let error = error.at_source(self.source);
+		self.iterator = Box::new(std::iter::once(Err(error)));
+		self
+	}
+
+	/// Find the first transformer [source column](Transform::source_cols) that doesn't exist
+	fn missing_source_col(&self, transformers: &[Box<dyn Transform + '_>]) -> Option<String> {
+		transformers
+			.iter()
+			.flat_map(|t| t.source_cols())
+			.find(|col| !self.headers.contains(col))
+			.map(String::from)
+	}
+
+	/// Write to the specified [`Target`].
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Target};
+	///
+	/// let mut csv = String::new();
+	/// Pipeline::from_path("test/AB.csv")
+	///   .unwrap()
+	///   .flush(Target::string(&mut csv))
+	///   .run()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "A,B\n1,2\n");
+	/// ```
+	pub fn flush(mut self, target: impl Target + 'a) -> Self {
+		let flush = Flush::new(self.iterator, target, self.source, self.headers.clone());
+		self.iterator = Box::new(flush);
+		self
+	}
+
+	/// Run the pipeline to completion, writing rows to `good` and errors to `bad` instead of stopping at the first error. Error rows have the columns of the pipeline followed by an `Error` column with the error. If the error wasn't caused by a specific row, or the row has a different number of fields, the fields are padded or truncated to fit.
+	///
+	/// Returns the number of good and bad rows. Only errors from writing to the targets stop the pipeline. If the pipeline already has an `Error` column, a [`DuplicateColumn`](Error::DuplicateColumn) error is returned before anything is written.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Error, Pipeline, Target};
+	///
+	/// let mut good = String::new();
+	/// let mut bad = String::new();
+	/// let counts = Pipeline::from_str("ID,Amount\n1,5\n2,x\n3,7\n")
+	///   .unwrap()
+	///   .validate_col("Amount", |field| match field.parse::<u32>() {
+	///     Ok(_) => Ok(()),
+	///     Err(_) => Err(Error::InvalidField(field.to_string())),
+	///   })
+	///   .partition_results(Target::string(&mut good), Target::string(&mut bad))
+	///   .unwrap();
+	///
+	/// assert_eq!(counts, (2, 1));
+	/// assert_eq!(good, "ID,Amount\n1,5\n3,7\n");
+	/// assert_eq!(bad, "ID,Amount,Error\n2,x,Invalid field x\n");
+	/// ```
+	pub fn partition_results(
+		self,
+		mut good: impl Target,
+		mut bad: impl Target,
+	) -> Result<(usize, usize), PlError> {
+		let source = self.source;
+		let width = self.headers.get_row().len();
+		let mut bad_headers = self.headers.clone();
+		if !bad_headers.push_field("Error") {
+			return Err(Error::DuplicateColumn("Error".to_string()).at_source(source));
+		}
+		let target_error = |e: csv::Error| Error::Csv(e).at_source(source);
+		good.write_headers(&self.headers).map_err(target_error)?;
+		bad.write_headers(&bad_headers).map_err(target_error)?;
+		let (mut good_count, mut bad_count) = (0, 0);
+		for row in self.build() {
+			match row {
+				Ok(row) => {
+					good.write_row(&row).map_err(target_error)?;
+					good_count += 1;
+				}
+				Err(e) => {
+					let mut bad_row = Row::with_capacity(0, width + 1);
+					let fields = e.row.iter().flat_map(|row| row.iter());
+					for field in fields.chain(std::iter::repeat("")).take(width) {
+						bad_row.push_field(field);
+					}
+					bad_row.push_field(&e.error.to_string());
+					bad.write_row(&bad_row).map_err(target_error)?;
+					bad_count += 1;
+				}
+			}
+		}
+		good.finish().map_err(target_error)?;
+		bad.finish().map_err(target_error)?;
+		Ok((good_count, bad_count))
+	}
+
+	/// Write each row to a target depending on its field in `col`, for example to split a file into one file per category. The closure is called with the field the first time each value is seen, and returns the target to write those rows to. Rows are passed through unchanged.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Target};
+	///
+	/// Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .partition_by("Country", |country| {
+	///     Box::new(Target::path(format!("target/doc/partition_by/{}.csv", country)))
+	///   })
+	///   .run()
+	///   .unwrap();
+	///
+	/// let norway = std::fs::read_to_string("target/doc/partition_by/Norway.csv").unwrap();
+	/// assert_eq!(norway, "ID,Country\n1,Norway\n");
+	/// let tuvalu = std::fs::read_to_string("target/doc/partition_by/Tuvalu.csv").unwrap();
+	/// assert_eq!(tuvalu, "ID,Country\n2,Tuvalu\n");
+	/// ```
+	pub fn partition_by<F>(mut self, col: &str, get_target: F) -> Self
+	where
+		F: FnMut(&str) -> Box<dyn Target + 'a> + 'a,
+	{
+		self.iterator = Box::new(PartitionBy {
+			iterator: self.iterator,
+			name: col.to_string(),
+			index: self.headers.get_index(col),
+			get_target,
+			targets: LinkedHashMap::new(),
+			headers: self.headers.clone(),
+			finished: false,
+			source: self.source,
+		});
+		self
+	}
+
+	/// Build and run a pipeline on a new thread, returning an iterator of its rows that can be sent to other threads.
+	///
+	/// Pipelines can't be sent between threads since their closures aren't required to be [`Send`], so instead the pipeline is built inside the `build` closure, which must be `Send + 'static`. Closures used by the pipeline's steps don't need to be `Send`. Up to 1024 rows are buffered ahead of the iterator.
+	///
+	/// Panics if `build` panics. If the pipeline panics while running, the panic is resumed when iterating.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let rows = Pipeline::spawn(|| {
+	///   Ok(Pipeline::from_path("test/Countries.csv")?.map_col("Country", |c| Ok(c.to_uppercase())))
+	/// })
+	/// .unwrap();
+	///
+	/// let countries = std::thread::spawn(move || {
+	///   rows.map(|row| row.unwrap()[1].to_string()).collect::<Vec<_>>()
+	/// });
+	/// assert_eq!(countries.join().unwrap(), vec!["NORWAY", "TUVALU"]);
+	/// ```
+	pub fn spawn<F>(build: F) -> Result<SendPipelineIter, PlError>
+	where
+		F: FnOnce() -> Result<Pipeline<'static>, PlError> + Send + 'static,
+	{
+		let (headers_sender, headers_receiver) = mpsc::channel();
+		let (sender, receiver) = mpsc::sync_channel(1024);
+		let handle = thread::spawn(move || {
+			let pipeline_iter = match build() {
+				Ok(pipeline) => pipeline.build(),
+				Err(e) => {
+					let _ = headers_sender.send(Err(e));
+					return;
+				}
+			};
+			if headers_sender
+				.send(Ok(pipeline_iter.headers.clone()))
+				.is_err()
+			{
+				return;
+			}
+			for row in pipeline_iter {
+				// Stop if the receiver has been dropped
+				if sender.send(row).is_err() {
+					return;
+				}
+			}
+		});
+		let headers = match headers_receiver.recv() {
+			Ok(headers) => headers?,
+			Err(_) => match handle.join() {
+				Err(panic) => panic::resume_unwind(panic),
+				Ok(()) => unreachable!(),
+			},
+		};
+		Ok(SendPipelineIter {
+			headers,
+			receiver,
+			handle: Some(handle),
+		})
+	}
+
+	/// The headers of the pipeline after the steps added so far, which you can use to decide which steps to add next.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let mut pipeline = Pipeline::from_path("test/Countries.csv").unwrap();
+	/// if !pipeline.headers().contains("Language") {
+	///   pipeline = pipeline.add_col("Language", |_headers, _row| Ok("Unknown".into()));
+	/// }
+	/// assert!(pipeline.headers().contains("Language"));
+	/// ```
+	pub fn headers(&self) -> &Headers {
+		&self.headers
+	}
+
+	/// Turn the pipeline into an iterator.
+	/// You can also do this using `pipeline.into_iter()`.
+	pub fn build(self) -> PipelineIter<'a> {
+		PipelineIter {
+			headers: self.headers,
+			iterator: Box::new(self.iterator),
+		}
+	}
+
+	/// Shorthand for `.build().run()`.
+	pub fn run(self) -> Result<(), PlError> {
+		self.build().run()
+	}
+
+	/// Run the pipeline and collect the data rows, without the header row. The headers are available from [`Pipeline::headers`] before calling this.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let rows = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .collect_rows()
+	///   .unwrap();
+	///
+	/// assert_eq!(rows.len(), 2);
+	/// assert_eq!(&rows[1][1], "Tuvalu");
+	/// ```
+	pub fn collect_rows(self) -> Result<Vec<Row>, PlError> {
+		self.build().collect()
+	}
+
+	/// Run the pipeline and collect the rows, starting with the header row.
+	pub fn collect_into_rows(self) -> Result<Vec<Row>, PlError> {
+		let pipeline_iter = self.build();
+		let header_row = pipeline_iter.headers.get_row().clone();
+		let records: Result<Vec<Row>, PlError> = pipeline_iter.collect();
+		let rows = vec![header_row].into_iter().chain(records?).collect();
+		Ok(rows)
+	}
+
+	/// Run the pipeline and collect the rows into a map, keyed by the value of `key_col`. If a key appears in more than one row, a [`DuplicateKey`](Error::DuplicateKey) error is returned.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let countries = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .collect_into_map("Country")
+	///   .unwrap();
+	///
+	/// assert_eq!(&countries["Tuvalu"][0], "2");
+	/// ```
+	pub fn collect_into_map(self, key_col: &str) -> Result<HashMap<String, Row>, PlError> {
+		self.collect_map(key_col, false)
+	}
+
+	/// Same as [`Pipeline::collect_into_map`], but when a key appears in more than one row, the last row is kept.
+	pub fn collect_into_map_keep_last(
+		self,
+		key_col: &str,
+	) -> Result<HashMap<String, Row>, PlError> {
+		self.collect_map(key_col, true)
+	}
+
+	fn collect_map(self, key_col: &str, keep_last: bool) -> Result<HashMap<String, Row>, PlError> {
+		let source = self.source;
+		let index = match self.headers.get_index(key_col) {
+			Some(index) => index,
+			None => return Err(Error::MissingColumn(key_col.to_string()).at_source(source)),
+		};
+		let mut map = HashMap::new();
+		for row in self.build() {
+			let row = row?;
+			let key = match row.get(index) {
+				Some(key) => key.to_string(),
+				None => return Err(Error::MissingColumn(key_col.to_string()).at_source(source)),
+			};
+			if !keep_last && map.contains_key(&key) {
+				return Err(Error::DuplicateKey(key).at_source(source));
+			}
+			map.insert(key, row);
+		}
+		Ok(map)
+	}
+
+	/// Run the pipeline and compute statistics for each column: the number of fields and non-empty fields, and for numeric columns the minimum, maximum, mean and sum.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let summary = Pipeline::from_str("Name,Score\na,1\nb,\nc,5\n")
+	///   .unwrap()
+	///   .describe()
+	///   .unwrap();
+	///
+	/// let score = summary.get("Score").unwrap();
+	/// assert_eq!(score.count, 3);
+	/// assert_eq!(score.non_empty, 2);
+	/// assert_eq!(score.numeric.as_ref().unwrap().mean, 3.0);
+	/// assert!(summary.get("Name").unwrap().numeric.is_none());
+	/// ```
+	pub fn describe(self) -> Result<Summary, PlError> {
+		let mut summary = Summary::new(&self.headers);
+		for row in self.build() {
+			summary.add_row(&row?);
+		}
+		Ok(summary)
+	}
+
+	/// Run the pipeline and count the empty fields of each column.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let counts = Pipeline::from_str("A,B\n1,\n,\n3,4\n")
+	///   .unwrap()
+	///   .count_empty_per_column()
+	///   .unwrap();
+	///
+	/// assert_eq!(counts["A"], 1);
+	/// assert_eq!(counts["B"], 2);
+	/// ```
+	pub fn count_empty_per_column(self) -> Result<HashMap<String, usize>, PlError> {
+		let names: Vec<String> = self.headers.into_iter().map(String::from).collect();
+		let mut counts = vec![0; names.len()];
+		for row in self.build() {
+			for (count, field) in counts.iter_mut().zip(&row?) {
+				if field.is_empty() {
+					*count += 1;
+				}
+			}
+		}
+		Ok(names.into_iter().zip(counts).collect())
+	}
+
+	/// Run the pipeline and find the keys that appear in more than one row, where the key is the fields of `cols`. Returns the first row of each duplicated key along with how many rows have that key, in the order the keys were first seen.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let duplicates = Pipeline::from_str("ID,Name\n1,a\n2,b\n1,c\n1,d\n")
+	///   .unwrap()
+	///   .find_duplicates(vec!["ID"])
+	///   .unwrap();
+	///
+	/// assert_eq!(duplicates.len(), 1);
+	/// let (row, count) = &duplicates[0];
+	/// assert_eq!(row, &vec!["1", "a"]);
+	/// assert_eq!(*count, 3);
+	/// ```
+	pub fn find_duplicates(self, cols: Vec<&str>) -> Result<Vec<(Row, usize)>, PlError> {
+		let source = self.source;
+		let mut indexes = Vec::with_capacity(cols.len());
+		for col in &cols {
+			match self.headers.get_index(col) {
+				Some(index) => indexes.push(index),
+				None => return Err(Error::MissingColumn(col.to_string()).at_source(source)),
+			}
+		}
+		let mut keys: LinkedHashMap<Vec<String>, (Row, usize)> = LinkedHashMap::new();
+		for row in self.build() {
+			let row = row?;
+			let mut key = Vec::with_capacity(indexes.len());
+			for (col, index) in cols.iter().zip(&indexes) {
+				match row.get(*index) {
+					Some(field) => key.push(field.to_string()),
+					None => return Err(Error::MissingColumn(col.to_string()).at_source(source)),
+				}
+			}
+			keys.entry(key).or_insert_with(|| (row, 0)).1 += 1;
+		}
+		Ok(keys
+			.into_iter()
+			.map(|(_, duplicate)| duplicate)
+			.filter(|(_, count)| *count > 1)
+			.collect())
+	}
+
+	/// Run both pipelines and compare their rows by key, where the key is the fields of `key_cols`. `self` is treated as the old version and `other` as the new version. Columns are compared by name, and columns that only exist in one of the pipelines count as changed. Every row of `self` is kept in memory.
+	///
+	/// If a key appears in more than one row of the same pipeline, a [`DuplicateKey`](Error::DuplicateKey) error is returned, with the key fields separated by commas.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let old = Pipeline::from_str("ID,Country,Capital\n1,Norway,Oslo\n2,Tuvalu,Funafuti\n").unwrap();
+	/// let new = Pipeline::from_str("ID,Country,Capital\n1,Norway,Bergen\n3,Chile,Santiago\n").unwrap();
+	/// let report = old.diff(new, vec!["ID"]).unwrap();
+	///
+	/// assert_eq!(report.added, vec![csv_pipeline::Row::from(vec!["3", "Chile", "Santiago"])]);
+	/// assert_eq!(report.removed[0], vec!["2", "Tuvalu", "Funafuti"]);
+	/// assert_eq!(report.changed[0].columns, vec!["Capital"]);
+	/// assert_eq!(report.changed[0].new, vec!["1", "Norway", "Bergen"]);
+	/// ```
+	pub fn diff(self, other: Pipeline<'a>, key_cols: Vec<&str>) -> Result<DiffReport, PlError> {
+		fn key_indexes(
+			headers: &Headers,
+			cols: &[&str],
+			source: usize,
+		) -> Result<Vec<(String, Option<usize>)>, PlError> {
+			let mut indexes = Vec::with_capacity(cols.len());
+			for col in cols {
+				match headers.get_index(col) {
+					Some(index) => indexes.push((col.to_string(), Some(index))),
+					None => return Err(Error::MissingColumn(col.to_string()).at_source(source)),
+				}
+			}
+			Ok(indexes)
+		}
+		let old_columns = key_indexes(&self.headers, &key_cols, self.source)?;
+		let new_columns = key_indexes(&other.headers, &key_cols, other.source)?;
+		let (old_source, new_source) = (self.source, other.source);
+		let old_headers = self.headers.clone();
+		let new_headers = other.headers.clone();
+
+		let mut old_rows: LinkedHashMap<Vec<String>, Row> = LinkedHashMap::new();
+		for row in self.build() {
+			let row = row?;
+			let key = row_key(&row, &old_columns).map_err(|e| e.at_row(old_source, &row))?;
+			if old_rows.contains_key(&key) {
+				return Err(Error::DuplicateKey(key.join(",")).at_row(old_source, &row));
+			}
+			old_rows.insert(key, row);
+		}
+
+		let mut report = DiffReport {
+			added: Vec::new(),
+			removed: Vec::new(),
+			changed: Vec::new(),
+		};
+		let mut seen = HashSet::new();
+		for row in other.build() {
+			let row = row?;
+			let key = row_key(&row, &new_columns).map_err(|e| e.at_row(new_source, &row))?;
+			if !seen.insert(key.clone()) {
+				return Err(Error::DuplicateKey(key.join(",")).at_row(new_source, &row));
+			}
+			match old_rows.remove(&key) {
+				None => report.added.push(row),
+				Some(old) => {
+					let columns = changed_columns(&old_headers, &old, &new_headers, &row);
+					if !columns.is_empty() {
+						report.changed.push(ChangedRow {
+							old,
+							new: row,
+							columns,
+						});
+					}
+				}
+			}
+		}
+		report.removed = old_rows.into_iter().map(|(_, row)| row).collect();
+		Ok(report)
+	}
+
+	pub fn collect_into_string(self) -> Result<String, PlError> {
+		let mut csv = String::new();
+		self.flush(StringTarget::new(&mut csv)).run()?;
+		Ok(csv)
+	}
+}
+/// Where a pipeline created from a path reads from
+pub(crate) struct FileSource {
+	path: PathBuf,
+	delimiter: u8,
+	options: ReaderOptions,
+}
+
+/// Unwrap the result of a `try_` method, for the methods that panic instead
+fn or_panic(result: Result<Pipeline, PlError>) -> Pipeline {
+	match result {
+		Ok(pipeline) => pipeline,
+		Err(e) => panic!("Error building pipeline source {}: {:?}", e.source, e.error),
+	}
+}
+
+/// Like [`or_panic`], but panics with the message from `message` for [`DuplicateColumn`](Error::DuplicateColumn) errors, so methods keep the panic messages they had before their `try_` variant existed
+fn or_panic_duplicate<M>(result: Result<Pipeline, PlError>, message: M) -> Pipeline
+where
+	M: FnOnce(&str) -> String,
+{
+	match result {
+		Err(PlError {
+			error: Error::DuplicateColumn(name),
+			..
+		}) => panic!("{}", message(&name)),
+		result => or_panic(result),
 	}
+}
 
-	/// Shorthand for `.build().run()`.
-	pub fn run(self) -> Result<(), PlError> {
-		self.build().run()
+/// Open a file, returning an [`Io`](Error::Io) error that includes the path if it fails
+fn open_file(path: &Path) -> Result<File, PlError> {
+	File::open(path).map_err(|e| {
+		let msg = format!("Could not open {}: {}", path.display(), e);
+		Error::Io(io::Error::new(e.kind(), msg)).at_source(0)
+	})
+}
+
+/// Pick the candidate delimiter that appears most often in the line, preferring earlier candidates on ties
+fn sniff_delimiter(line: &str) -> u8 {
+	let mut best = (b',', 0);
+	for delimiter in [b',', b'\t', b';', b'|'] {
+		let count = line.bytes().filter(|byte| *byte == delimiter).count();
+		if count > best.1 {
+			best = (delimiter, count);
+		}
 	}
+	best.0
+}
 
-	pub fn collect_into_rows(self) -> Result<Vec<Row>, PlError> {
-		let pipeline_iter = self.build();
-		let header_row = pipeline_iter.headers.get_row().clone();
-		let records: Result<Vec<Row>, PlError> = pipeline_iter.map(|record| record).collect();
-		let rows = vec![header_row]
-			.into_iter()
-			.chain(records?.into_iter())
-			.collect();
-		Ok(rows)
+/// 64-bit FNV-1a hash, which unlike `DefaultHasher` is stable across Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+	let mut hash: u64 = 0xcbf29ce484222325;
+	for byte in bytes {
+		hash ^= *byte as u64;
+		hash = hash.wrapping_mul(0x100000001b3);
 	}
+	hash
+}
 
-	pub fn collect_into_string(self) -> Result<String, PlError> {
-		let mut csv = String::new();
-		self.flush(StringTarget::new(&mut csv)).run()?;
-		Ok(csv)
+/// Parse the fields at `index` as `f64`
+fn parse_col(rows: &[Row], index: usize, col: &str) -> Result<Vec<f64>, Error> {
+	let mut values = Vec::with_capacity(rows.len());
+	for row in rows {
+		let field = row
+			.get(index)
+			.ok_or_else(|| Error::MissingColumn(col.to_string()))?;
+		let value: f64 = field
+			.parse()
+			.map_err(|_| Error::InvalidField(field.to_string()))?;
+		values.push(value);
 	}
+	Ok(values)
 }
+
 impl<'a> IntoIterator for Pipeline<'a> {
 	type Item = RowResult;
 	type IntoIter = PipelineIter<'a>;
@@ -500,11 +3520,16 @@ pub struct PipelineIter<'a> {
 }
 
 impl<'a> PipelineIter<'a> {
+	/// The headers of the rows returned by the iterator
+	pub fn headers(&self) -> &Headers {
+		&self.headers
+	}
+
 	/// Advances the iterator until an error is found.
 	///
 	/// Returns `None` when the iterator is finished.
 	pub fn next_error(&mut self) -> Option<PlError> {
-		while let Some(item) = self.next() {
+		for item in self.by_ref() {
 			if let Err(err) = item {
 				return Some(err);
 			}
@@ -514,11 +3539,86 @@ impl<'a> PipelineIter<'a> {
 
 	/// Run through the whole iterator. Returns the first error found, if any
 	pub fn run(&mut self) -> Result<(), PlError> {
-		while let Some(item) = self.next() {
+		for item in self.by_ref() {
 			item?;
 		}
 		Ok(())
 	}
+
+	/// Fold every row into an accumulator, stopping at the first error from either the pipeline or the closure.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	///
+	/// let total_length = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .build()
+	///   .try_fold_rows(0, |total, row| Ok(total + row[1].len()))
+	///   .unwrap();
+	///
+	/// assert_eq!(total_length, 12);
+	/// ```
+	pub fn try_fold_rows<A, F>(&mut self, init: A, mut f: F) -> Result<A, PlError>
+	where
+		F: FnMut(A, Row) -> Result<A, PlError>,
+	{
+		let mut acc = init;
+		for row in self.by_ref() {
+			acc = f(acc, row?)?;
+		}
+		Ok(acc)
+	}
+
+	/// Turn the iterator into a [`Stream`](futures::Stream). Each row is processed when the stream is polled, which blocks the current task, so for large inputs you may want to run the stream on a thread meant for blocking work.
+	///
+	/// Requires the `futures` feature.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	/// use futures::StreamExt;
+	///
+	/// let stream = Pipeline::from_path("test/AB.csv").unwrap().build().into_stream();
+	/// let rows: Vec<_> = futures::executor::block_on(stream.collect());
+	/// assert_eq!(rows.len(), 1);
+	/// ```
+	#[cfg(feature = "futures")]
+	pub fn into_stream(self) -> impl futures::Stream<Item = RowResult> + 'a {
+		futures::stream::iter(self)
+	}
+	/// Turn the iterator into a reader of CSV bytes, starting with the header row. Rows are serialized as they are read, so the output is never held in memory all at once.
+	///
+	/// [`Io`](Error::Io) errors are returned as they are. Other pipeline errors are returned as [`io::Error`]s of kind [`InvalidData`](io::ErrorKind::InvalidData) that wrap the [`PlError`], which you can get back using [`io::Error::into_inner`] and downcasting.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::Pipeline;
+	/// use std::io::Read;
+	///
+	/// let mut reader = Pipeline::from_path("test/AB.csv").unwrap().build().into_csv_reader();
+	/// let mut csv = String::new();
+	/// reader.read_to_string(&mut csv).unwrap();
+	///
+	/// assert_eq!(csv, "A,B\n1,2\n");
+	/// ```
+	pub fn into_csv_reader(self) -> CsvReader<'a> {
+		let buffer = Rc::new(RefCell::new(Vec::new()));
+		let mut writer = csv::Writer::from_writer(SharedBuffer(buffer.clone()));
+		let header_result = writer
+			.write_record(self.headers.get_row())
+			.and_then(|_| Ok(writer.flush()?));
+		CsvReader {
+			rows: self,
+			writer,
+			buffer,
+			position: 0,
+			error: header_result.err().map(io::Error::from),
+		}
+	}
 }
 impl<'a> Iterator for PipelineIter<'a> {
 	type Item = RowResult;
@@ -528,28 +3628,338 @@ impl<'a> Iterator for PipelineIter<'a> {
 	}
 }
 
+/// Reads a pipeline's output as CSV bytes. You can get one using [`PipelineIter::into_csv_reader`].
+pub struct CsvReader<'a> {
+	rows: PipelineIter<'a>,
+	writer: csv::Writer<SharedBuffer>,
+	buffer: Rc<RefCell<Vec<u8>>>,
+	/// How much of `buffer` has been read
+	position: usize,
+	error: Option<io::Error>,
+}
+impl<'a> io::Read for CsvReader<'a> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		if let Some(err) = self.error.take() {
+			return Err(err);
+		}
+		loop {
+			{
+				let data = self.buffer.borrow();
+				if self.position < data.len() {
+					let len = buf.len().min(data.len() - self.position);
+					buf[..len].copy_from_slice(&data[self.position..self.position + len]);
+					self.position += len;
+					return Ok(len);
+				}
+			}
+			RefCell::borrow_mut(&self.buffer).clear();
+			self.position = 0;
+			match self.rows.next() {
+				Some(Ok(row)) => {
+					self.writer.write_record(&row)?;
+					self.writer.flush()?;
+				}
+				Some(Err(PlError {
+					error: Error::Io(err),
+					..
+				})) => return Err(err),
+				Some(Err(err)) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+				None => return Ok(0),
+			}
+		}
+	}
+}
+
+/// Writer whose output can be read while it's still owned by a [`csv::Writer`]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+impl io::Write for SharedBuffer {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		RefCell::borrow_mut(&self.0).extend_from_slice(buf);
+		Ok(buf.len())
+	}
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// A pipeline running on another thread, which you can get using [`Pipeline::spawn`]. Unlike [`PipelineIter`], this can be sent between threads.
+pub struct SendPipelineIter {
+	pub headers: Headers,
+	receiver: Receiver<RowResult>,
+	handle: Option<JoinHandle<()>>,
+}
+impl SendPipelineIter {
+	/// Run through the whole iterator. Returns the first error found, if any
+	pub fn run(&mut self) -> Result<(), PlError> {
+		for item in self.by_ref() {
+			item?;
+		}
+		Ok(())
+	}
+}
+impl Iterator for SendPipelineIter {
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		match self.receiver.recv() {
+			Ok(row) => Some(row),
+			Err(_) => {
+				if let Some(Err(panic)) = self.handle.take().map(JoinHandle::join) {
+					panic::resume_unwind(panic);
+				}
+				None
+			}
+		}
+	}
+}
+
 pub struct RowIter<R: io::Read> {
 	inner: StringRecordsIntoIter<R>,
 	source: usize,
+	null_token: Option<String>,
+	skip_empty_rows: bool,
+	max_field_size: Option<usize>,
+	max_row_size: Option<usize>,
 }
 impl<R: io::Read> RowIter<R> {
 	pub fn from_records(source: usize, records: StringRecordsIntoIter<R>) -> Self {
 		RowIter {
 			source,
 			inner: records,
+			null_token: None,
+			skip_empty_rows: false,
+			max_field_size: None,
+			max_row_size: None,
+		}
+	}
+	/// Replace fields matching the token with empty fields
+	pub fn null_token(mut self, null_token: Option<String>) -> Self {
+		self.null_token = null_token;
+		self
+	}
+	/// Skip rows where every field is empty
+	pub fn skip_empty_rows(mut self, skip_empty_rows: bool) -> Self {
+		self.skip_empty_rows = skip_empty_rows;
+		self
+	}
+	/// Return an error for fields or rows that are larger than these numbers of bytes
+	pub fn max_sizes(mut self, max_field_size: Option<usize>, max_row_size: Option<usize>) -> Self {
+		self.max_field_size = max_field_size;
+		self.max_row_size = max_row_size;
+		self
+	}
+	fn csv_error(err: csv::Error, max_row_size: Option<usize>) -> Error {
+		match max_row_size {
+			Some(max_row_size) if is_row_size_exceeded(&err) => Error::RowTooLarge(max_row_size),
+			_ => Error::Csv(err),
 		}
 	}
+	/// Check the size limits of a row that ended at `end` bytes into the input
+	fn check_sizes(&self, row: &Row, start: u64, end: u64) -> Result<(), Error> {
+		if let Some(max_row_size) = self.max_row_size {
+			if end - start > max_row_size as u64 {
+				return Err(Error::RowTooLarge(max_row_size));
+			}
+		}
+		if let Some(max_field_size) = self.max_field_size {
+			if row.iter().any(|field| field.len() > max_field_size) {
+				return Err(Error::FieldTooLarge(max_field_size));
+			}
+		}
+		Ok(())
+	}
 }
 impl<R: io::Read> Iterator for RowIter<R> {
 	type Item = RowResult;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		self.inner.next().map(|result| {
-			result.map_err(|err| {
-				return Error::Csv(err).at_source(self.source);
-			})
+		let row = loop {
+			let start = self.inner.reader().position().byte();
+			let row = match self.inner.next()? {
+				Ok(row) => row,
+				Err(err) => {
+					let err = Self::csv_error(err, self.max_row_size);
+					return Some(Err(err.at_source(self.source)));
+				}
+			};
+			let end = self.inner.reader().position().byte();
+			if let Err(e) = self.check_sizes(&row, start, end) {
+				return Some(Err(e.at_source(self.source)));
+			}
+			if !(self.skip_empty_rows && row.iter().all(str::is_empty)) {
+				break row;
+			}
+		};
+		match &self.null_token {
+			Some(token) if row.iter().any(|field| field == token) => Some(Ok(row
+				.iter()
+				.map(|field| if field == token { "" } else { field })
+				.collect())),
+			_ => Some(Ok(row)),
+		}
+	}
+}
+
+#[test]
+fn collect_into_map_duplicate() {
+	let source = "A,B\n1,x\n1,y\n";
+	let err = Pipeline::from_str(source)
+		.unwrap()
+		.collect_into_map("A")
+		.unwrap_err();
+	assert!(matches!(err.error, Error::DuplicateKey(key) if key == "1"));
+	let map = Pipeline::from_str(source)
+		.unwrap()
+		.collect_into_map_keep_last("A")
+		.unwrap();
+	assert_eq!(&map["1"][1], "y");
+}
+
+#[test]
+fn union_by_name_short_row() {
+	let short = csv::ReaderBuilder::new()
+		.flexible(true)
+		.from_reader("B,C\n3\n".as_bytes());
+	let err = Pipeline::union_by_name(vec![
+		Pipeline::from_str("A,B\n1,2\n").unwrap(),
+		Pipeline::from_reader(short).unwrap(),
+	])
+	.run()
+	.unwrap_err();
+	assert_eq!(err.source, 1);
+	assert!(matches!(err.error, Error::MissingColumn(col) if col == "C"));
+}
+
+#[test]
+fn trailing_empty_rows() {
+	use crate::{ReaderOptions, Transformer};
+
+	let count = |source: &str, options: ReaderOptions| {
+		let reader = csv::Reader::from_reader(source.as_bytes());
+		Pipeline::from_reader_with(reader, options)
+			.unwrap()
+			.transform_into(|| vec![Transformer::new("Count").count()])
+			.collect_into_string()
+			.unwrap()
+	};
+	assert_eq!(count("A,B\n1,2\n\n\n", ReaderOptions::new()), "Count\n1\n");
+	assert_eq!(count("A,B\n1,2\n,\n", ReaderOptions::new()), "Count\n2\n");
+	let options = ReaderOptions::new().skip_empty_rows();
+	assert_eq!(count("A,B\n1,2\n,\n", options), "Count\n1\n");
+}
+
+#[test]
+fn map_col_lookup_strict() {
+	let table = HashMap::from([("NO".to_string(), "Norway".to_string())]);
+	let err = Pipeline::from_str("Code\nNO\nTV\n")
+		.unwrap()
+		.map_col_lookup_strict("Code", table)
+		.collect_into_string()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::InvalidField(field) if field == "TV"));
+}
+
+#[test]
+fn transform_missing_source_col() {
+	use crate::Transformer;
+
+	// Fails before reading any rows, even though the input is empty
+	let err = Pipeline::from_str("A,B\n")
+		.unwrap()
+		.transform_into(|| vec![Transformer::new("C").sum(0)])
+		.run()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::MissingColumn(col) if col == "C"));
+
+	// Every input column is checked, not just the first
+	let err = Pipeline::from_str("A,B\n")
+		.unwrap()
+		.transform_into(|| vec![Transformer::new("M").weighted_mean("A", "C")])
+		.run()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::MissingColumn(col) if col == "C"));
+}
+
+#[test]
+fn transform_duplicate_names() {
+	use crate::Transformer;
+
+	let err = Pipeline::from_path("test/AB.csv")
+		.unwrap()
+		.transform_into(|| {
+			vec![
+				Transformer::new("A").keep_unique(),
+				Transformer::new("A").from_col("B").sum(0),
+			]
+		})
+		.run()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::DuplicateColumn(col) if col == "A"));
+}
+
+#[test]
+fn error_keeps_row() {
+	let err = Pipeline::from_path("test/Countries.csv")
+		.unwrap()
+		.validate_col("Country", |field| match field {
+			"Tuvalu" => Err(Error::InvalidField(field.to_string())),
+			_ => Ok(()),
 		})
+		.run()
+		.unwrap_err();
+	assert_eq!(err.row, Some(Row::from(vec!["2", "Tuvalu"])));
+}
+
+#[test]
+fn set_headers_wrong_count() {
+	// Fails before reading any rows, even though the input is empty
+	let err = Pipeline::from_str("A,B\n")
+		.unwrap()
+		.set_headers(vec!["X"])
+		.run()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::MismatchedHeaders(..)));
+}
+
+#[test]
+fn buffer_capacity() {
+	use crate::ReaderOptions;
+
+	let path = std::env::temp_dir().join("csv-pipeline-test-buffer-capacity.csv");
+	let mut source = String::from("A,B\n");
+	for i in 0..10_000 {
+		source += &format!("{},{}\n", i, "x".repeat(i % 100));
 	}
+	std::fs::write(&path, &source).unwrap();
+	for capacity in [1, 64, 1 << 20] {
+		let options = ReaderOptions::new().buffer_capacity(capacity);
+		let csv = Pipeline::from_path_with(&path, options)
+			.unwrap()
+			.collect_into_string()
+			.unwrap();
+		assert_eq!(csv, source);
+	}
+	std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn from_path_not_found() {
+	let err = Pipeline::from_path("test/Missing.csv").err().unwrap();
+	match err.error {
+		Error::Io(e) => {
+			assert_eq!(e.kind(), io::ErrorKind::NotFound);
+			assert!(e.to_string().contains("test/Missing.csv"));
+		}
+		_ => panic!("Expected an Io error"),
+	}
+}
+
+#[test]
+fn test_sniff_delimiter() {
+	assert_eq!(sniff_delimiter("A,B;C,D\n"), b',');
+	assert_eq!(sniff_delimiter("A\tB\tC\n"), b'\t');
+	assert_eq!(sniff_delimiter("A|B\n"), b'|');
+	assert_eq!(sniff_delimiter("A\n"), b',');
 }
 
 #[test]
@@ -571,3 +3981,416 @@ fn from_pipelines_mismatch() {
 		_ => panic!("Expected MismatchedHeaders"),
 	}
 }
+
+#[test]
+fn aggregate_empty() {
+	use crate::Transformer;
+
+	let csv = Pipeline::from_rows(vec![Row::from(vec!["A", "B"])])
+		.unwrap()
+		.aggregate(vec![
+			Transformer::new("Sum").from_col("A").sum(0),
+			Transformer::new("Count").count(),
+		])
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "Sum,Count\n0,0\n");
+}
+
+#[test]
+fn spawn_is_send() {
+	fn assert_send<T: Send>(_: &T) {}
+
+	let mut rows = Pipeline::spawn(|| Pipeline::from_path("test/AB.csv")).unwrap();
+	assert_send(&rows);
+	assert_eq!(rows.headers.get_row(), &Row::from(vec!["A", "B"]));
+	rows.run().unwrap();
+
+	let result = Pipeline::spawn(|| Pipeline::from_path("test/Missing.csv"));
+	assert!(matches!(
+		result,
+		Err(PlError {
+			error: Error::Io(_),
+			..
+		})
+	));
+}
+
+#[test]
+fn max_row_size_from_path() {
+	let options = ReaderOptions::new().max_row_size(5);
+	let err = Pipeline::from_path_with("test/Countries.csv", options)
+		.err()
+		.unwrap();
+	assert!(matches!(err.error, Error::RowTooLarge(5)));
+
+	let options = ReaderOptions::new().max_row_size(11).max_field_size(5);
+	let err = Pipeline::from_path_with("test/Countries.csv", options)
+		.unwrap()
+		.collect_into_string()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::FieldTooLarge(5)));
+}
+
+#[test]
+fn branch_filter_keeps_order() {
+	let csv = Pipeline::from_str("N\n1\n2\n3\n")
+		.unwrap()
+		.branch(
+			|headers, row| headers.get_field(row, "N") != Some("2"),
+			|pipeline| pipeline.filter_col("N", |n| n != "1"),
+			|pipeline| pipeline,
+		)
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "N\n2\n3\n");
+
+	let csv = Pipeline::from_str("N\n1\n2\n3\n4\n5\n")
+		.unwrap()
+		.branch(
+			|headers, row| headers.get_field(row, "N").unwrap() < "3",
+			|pipeline| pipeline,
+			|pipeline| pipeline.filter_col("N", |n| n == "5"),
+		)
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "N\n1\n2\n5\n");
+}
+
+#[test]
+fn branch_buffered() {
+	let csv = Pipeline::from_str("N\n3\n1\n4\n2\n")
+		.unwrap()
+		.branch(
+			|headers, row| headers.get_field(row, "N").unwrap() >= "3",
+			|pipeline| pipeline.sort_by("N", Order::Asc, SortKey::Numeric),
+			|pipeline| pipeline.filter_col("N", |n| n != "2"),
+		)
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "N\n1\n3\n4\n");
+
+	let err = Pipeline::from_str("A,B\n1,2\n")
+		.unwrap()
+		.branch(
+			|_, _| true,
+			|pipeline| pipeline,
+			|pipeline| pipeline.select(vec!["A"]),
+		)
+		.collect_into_string()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::MismatchedHeaders(..)));
+}
+
+#[test]
+fn add_col_cached_max_entries() {
+	let mut calls = 0;
+	let csv = Pipeline::from_str("K\na\nb\na\na\n")
+		.unwrap()
+		.add_col_cached("V", vec!["K"], Some(1), |headers, row| {
+			calls += 1;
+			Ok(headers.get_field(row, "K").unwrap().to_uppercase())
+		})
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "K,V\na,A\nb,B\na,A\na,A\n");
+	assert_eq!(calls, 3);
+
+	let mut calls = 0;
+	let csv = Pipeline::from_str("K\na\na\n")
+		.unwrap()
+		.add_col_cached("V", vec!["K"], Some(0), |headers, row| {
+			calls += 1;
+			Ok(headers.get_field(row, "K").unwrap().to_uppercase())
+		})
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "K,V\na,A\na,A\n");
+	assert_eq!(calls, 2);
+}
+
+#[test]
+fn try_methods() {
+	let err = Pipeline::from_path("test/AB.csv")
+		.unwrap()
+		.try_rename_col("X", "Y")
+		.err()
+		.unwrap();
+	assert!(matches!(err.error, Error::MissingColumn(col) if col == "X"));
+
+	let err = Pipeline::from_path("test/AB.csv")
+		.unwrap()
+		.try_rename_cols(|_, _| "X")
+		.err()
+		.unwrap();
+	assert!(matches!(err.error, Error::DuplicateColumn(col) if col == "X"));
+
+	let csv = Pipeline::from_path("test/AB.csv")
+		.unwrap()
+		.try_select_indices(vec![1])
+		.unwrap()
+		.try_set_headers(vec!["X"])
+		.unwrap()
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "X\n2\n");
+
+	let err = Pipeline::from_path("test/AB.csv")
+		.unwrap()
+		.try_set_headers(vec!["X"])
+		.err()
+		.unwrap();
+	assert!(matches!(err.error, Error::MismatchedHeaders(..)));
+
+	let err = Pipeline::from_path("test/AB.csv")
+		.unwrap()
+		.try_select_indices(vec![0, 2])
+		.err()
+		.unwrap();
+	assert!(matches!(err.error, Error::MissingColumn(_)));
+
+	// Fails before reading any rows, even though the input is empty
+	let err = Pipeline::from_str("A,B\n")
+		.unwrap()
+		.select_indices(vec![2])
+		.run()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::MissingColumn(_)));
+
+	let err = Pipeline::from_path("test/DoublePipe.txt").err().unwrap();
+	assert!(matches!(err.error, Error::Io(e) if e.kind() == io::ErrorKind::InvalidInput));
+}
+
+#[test]
+fn interpolate_col_edges() {
+	let csv = Pipeline::from_str("N,V\n1,\n2,2\n3,\n4,3\n5,\n")
+		.unwrap()
+		.interpolate_col("V", false)
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "N,V\n1,\n2,2\n3,2.5\n4,3\n5,\n");
+
+	let rows: Vec<_> = Pipeline::from_str("N,V\n1,1\n2,\n3,x\n4,3\n")
+		.unwrap()
+		.interpolate_col("V", false)
+		.build()
+		.collect();
+	assert_eq!(rows.len(), 4);
+	assert_eq!(rows[1].as_ref().unwrap(), &vec!["2", "2"]);
+	assert!(matches!(
+		rows[2].as_ref().unwrap_err().error,
+		Error::InvalidField(_)
+	));
+
+	// A short row inside a gap stays in place
+	let headers = Headers::from_row(Row::from(vec!["N", "V"])).unwrap();
+	let source = vec![vec!["1", "1"], vec!["2", ""], vec!["3"], vec!["4", "3"]];
+	let rows: Vec<_> =
+		Pipeline::from_iter_with_headers(headers, source.into_iter().map(|row| Ok(Row::from(row))))
+			.interpolate_col("V", false)
+			.build()
+			.collect();
+	assert_eq!(rows.len(), 4);
+	assert_eq!(rows[1].as_ref().unwrap(), &vec!["2", "2"]);
+	assert!(matches!(
+		rows[2].as_ref().unwrap_err().error,
+		Error::MissingColumn(_)
+	));
+	assert_eq!(rows[3].as_ref().unwrap(), &vec!["4", "3"]);
+}
+
+#[test]
+fn transform_sorted_error_keeps_group() {
+	use crate::Transformer;
+
+	let rows: Vec<_> = Pipeline::from_str("Person,Score\nA,1\nA,2\nB,x\nB,4\n")
+		.unwrap()
+		.transform_sorted(vec!["Person"], || {
+			vec![Transformer::new("Total").from_col("Score").sum(0)]
+		})
+		.build()
+		.collect();
+	assert_eq!(rows.len(), 3);
+	assert_eq!(rows[0].as_ref().unwrap(), &vec!["A", "3"]);
+	assert!(matches!(
+		rows[1].as_ref().unwrap_err().error,
+		Error::InvalidField(_)
+	));
+	assert_eq!(rows[2].as_ref().unwrap(), &vec!["B", "4"]);
+}
+
+#[test]
+fn add_source_col_after_buffering() {
+	let merged = || {
+		Pipeline::from_pipelines(vec![
+			Pipeline::from_str("A\n2\n").unwrap(),
+			Pipeline::from_str("A\n1\n").unwrap(),
+		])
+	};
+	let csv = merged()
+		.add_source_col("Before")
+		.sort_by("A", Order::Asc, SortKey::Numeric)
+		.add_source_col("After")
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "A,Before,After\n1,1,0\n2,0,0\n");
+
+	let csv = merged()
+		.lead_col("A", "Next", 1)
+		.add_source_col("Source")
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "A,Next,Source\n2,1,0\n1,,0\n");
+}
+
+#[test]
+fn add_existing_col() {
+	let adders: Vec<fn(Pipeline<'static>) -> Pipeline<'static>> = vec![
+		|p| p.rank_col("A", "B", Order::Asc),
+		|p| p.dense_rank_col("A", "B", Order::Asc),
+		|p| p.rank_within(vec!["A"], "A", "B", Order::Asc),
+		|p| p.lag_col("A", "B", 1),
+		|p| p.lead_col("A", "B", 1),
+		|p| p.add_col("B", |_, _| Ok(String::new())),
+		|p| p.add_col_ctx("B", |_, _| Ok(String::new())),
+		|p| p.add_col_cached("B", vec!["A"], None, |_, _| Ok(String::new())),
+		|p| p.add_const_col("B", "x"),
+		|p| p.add_source_col("B"),
+		|p| p.flag_col("B", |_, _| true),
+		|p| p.case_col("B", vec![], "x"),
+		|p| p.concat_col("B", vec!["A"], "-"),
+		|p| p.bucket_col("A", 3, "B"),
+	];
+	for add in adders {
+		let err = add(Pipeline::from_str("A,B\n1,2\n").unwrap())
+			.run()
+			.unwrap_err();
+		assert!(matches!(err.error, Error::DuplicateColumn(col) if col == "B"));
+	}
+	#[cfg(feature = "rand")]
+	{
+		let err = Pipeline::from_str("A,B\n1,2\n")
+			.unwrap()
+			.add_split_col("B", 0.5, 1)
+			.run()
+			.unwrap_err();
+		assert!(matches!(err.error, Error::DuplicateColumn(col) if col == "B"));
+	}
+
+	let err = Pipeline::from_str("A,B\n1,2\n")
+		.unwrap()
+		.add_pct_of_total("A", "B")
+		.run()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::DuplicateColumn(col) if col == "B"));
+
+	let err = Pipeline::from_str("A,B\n1,2\n")
+		.unwrap()
+		.running_max("A", "B")
+		.run()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::DuplicateColumn(col) if col == "B"));
+
+	let err = Pipeline::from_str("A,B\n1,2\n")
+		.unwrap()
+		.moving_average("A", 2, "B", true)
+		.run()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::DuplicateColumn(col) if col == "B"));
+}
+
+#[test]
+fn partition_by_finishes_every_target() {
+	struct FailingTarget(Rc<RefCell<usize>>);
+	impl Target for FailingTarget {
+		fn write_headers(&mut self, _headers: &Headers) -> Result<(), csv::Error> {
+			Ok(())
+		}
+		fn write_row(&mut self, _row: &Row) -> Result<(), csv::Error> {
+			Ok(())
+		}
+		fn finish(&mut self) -> Result<(), csv::Error> {
+			*RefCell::borrow_mut(&self.0) += 1;
+			Err(io::Error::other("finish failed").into())
+		}
+	}
+
+	let finished = Rc::new(RefCell::new(0));
+	let err = Pipeline::from_path("test/Countries.csv")
+		.unwrap()
+		.partition_by("Country", |_| Box::new(FailingTarget(finished.clone())))
+		.run()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::Csv(_)));
+	assert_eq!(*finished.borrow(), 2);
+}
+
+#[test]
+fn swap_missing_col() {
+	// Fails before reading any rows, even though the input is empty
+	let err = Pipeline::from_str("A,B\n")
+		.unwrap()
+		.swap_cols("A", "C")
+		.run()
+		.unwrap_err();
+	assert!(matches!(err.error, Error::MissingColumn(col) if col == "C"));
+}
+
+#[test]
+fn csv_reader_error() {
+	use std::io::Read;
+
+	let mut reader = Pipeline::from_str("A\n1\nx\n")
+		.unwrap()
+		.validate_col("A", |field| match field {
+			"x" => Err(Error::InvalidField(field.to_string())),
+			_ => Ok(()),
+		})
+		.build()
+		.into_csv_reader();
+	let err = reader.read_to_string(&mut String::new()).unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+	let err = err.into_inner().unwrap().downcast::<PlError>().unwrap();
+	assert!(matches!(err.error, Error::InvalidField(field) if field == "x"));
+}
+
+#[test]
+fn lead_col_error_order() {
+	let headers = Headers::from_row(Row::from(vec!["N"])).unwrap();
+	let source = vec![
+		Ok(Row::from(vec!["1"])),
+		Err(Error::InvalidField("x".to_string()).at_source(0)),
+		Ok(Row::from(vec!["2"])),
+	];
+	let rows: Vec<_> = Pipeline::from_iter_with_headers(headers, source.into_iter())
+		.lead_col("N", "Next", 1)
+		.build()
+		.collect();
+	assert_eq!(rows.len(), 3);
+	assert_eq!(rows[0].as_ref().unwrap(), &vec!["1", "2"]);
+	assert!(matches!(
+		rows[1].as_ref().unwrap_err().error,
+		Error::InvalidField(_)
+	));
+	assert_eq!(rows[2].as_ref().unwrap(), &vec!["2", ""]);
+}
+
+#[test]
+fn partition_results_error_col_exists() {
+	let mut good = String::new();
+	let mut bad = String::new();
+	let err = Pipeline::from_str("ID,Error\n1,x\n")
+		.unwrap()
+		.partition_results(StringTarget::new(&mut good), StringTarget::new(&mut bad))
+		.unwrap_err();
+	assert!(matches!(err.error, Error::DuplicateColumn(col) if col == "Error"));
+	assert_eq!(bad, "");
+}
+
+#[test]
+#[should_panic(expected = "Error renaming column in source 0: Duplicate column at index 1")]
+fn rename_col_panic_message() {
+	Pipeline::from_path("test/AB.csv")
+		.unwrap()
+		.rename_col("A", "B");
+}
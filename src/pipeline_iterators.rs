@@ -1,14 +1,23 @@
 use super::headers::Headers;
+use crate::options::RowHasher;
+use crate::spill::Spill;
 use crate::target::Target;
-use crate::transform::{compute_hash, Transform};
-use crate::{Error, Pipeline, PipelineIter, Row, RowResult};
+use crate::transform::{compute_key, GroupKey, Transform};
+use crate::{Error, Pipeline, PipelineIter, PlError, Row, RowResult};
 use linked_hash_map::{Entry, LinkedHashMap};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::Rc;
 
 pub struct PipelinesChain<'a, P> {
 	pub pipelines: P,
 	pub current: Option<PipelineIter<'a>>,
 	pub index: usize,
 	pub headers: Headers,
+	/// Label of the current pipeline, for [`Pipeline::add_source_col`]
+	pub label: Rc<RefCell<String>>,
 }
 impl<'a, P> Iterator for PipelinesChain<'a, P>
 where
@@ -18,19 +27,17 @@ where
 
 	fn next(&mut self) -> Option<Self::Item> {
 		// If current is None, iteration is done
-		match self.current.as_mut()?.next() {
-			Some(mut row) => {
-				if let Err(e) = row.as_mut() {
-					e.source = self.index;
-				}
-				return Some(row);
+		if let Some(mut row) = self.current.as_mut()?.next() {
+			if let Err(e) = row.as_mut() {
+				e.source = self.index;
 			}
-			None => {}
-		};
+			return Some(row);
+		}
 		// If current was done, go to the next pipeline
 		match self.pipelines.next() {
 			Some(pipeline) => {
 				self.index += 1;
+				*self.label.borrow_mut() = pipeline.label(self.index);
 				self.current = Some(pipeline.build());
 				let current = self.current.as_mut().unwrap();
 				if current.headers.get_row() != self.headers.get_row() {
@@ -73,7 +80,7 @@ where
 				row.push_field(&value);
 				Some(Ok(row))
 			}
-			Err(e) => Some(Err(e.at_source(self.source))),
+			Err(e) => Some(Err(e.at_row(self.source, &row))),
 		}
 	}
 }
@@ -103,6 +110,180 @@ where
 	}
 }
 
+pub struct Stateful<I, S, F: FnMut(RowResult, &mut S) -> RowResult> {
+	pub iterator: I,
+	pub state: S,
+	pub f: F,
+}
+impl<I, S, F> Iterator for Stateful<I, S, F>
+where
+	I: Iterator<Item = RowResult>,
+	F: FnMut(RowResult, &mut S) -> RowResult,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let row = self.iterator.next()?;
+		Some((self.f)(row, &mut self.state))
+	}
+}
+
+/// State shared between the two branches of [`Pipeline::branch`](crate::Pipeline::branch)
+pub struct BranchShared<I, P> {
+	pub iterator: I,
+	pub predicate: P,
+	pub headers: Headers,
+	/// Rows waiting to be read by each branch, along with their position in the input
+	pub queues: [VecDeque<(usize, RowResult)>; 2],
+	pub position: usize,
+	/// Input position of the last row read by each branch
+	pub taken: [usize; 2],
+}
+impl<I, P> BranchShared<I, P>
+where
+	I: Iterator<Item = RowResult>,
+	P: FnMut(&Headers, &Row) -> bool,
+{
+	/// Read a row and add it to the queue of its branch. Errors go to the first branch. Returns false at the end of the input.
+	fn pull(&mut self) -> bool {
+		let row = match self.iterator.next() {
+			Some(row) => row,
+			None => return false,
+		};
+		let branch = match &row {
+			Ok(row) if !(self.predicate)(&self.headers, row) => 1,
+			_ => 0,
+		};
+		self.queues[branch].push_back((self.position, row));
+		self.position += 1;
+		true
+	}
+}
+
+/// The input of one branch of [`Pipeline::branch`](crate::Pipeline::branch)
+pub struct BranchInput<I, P> {
+	pub shared: Rc<RefCell<BranchShared<I, P>>>,
+	pub branch: usize,
+}
+impl<I, P> Iterator for BranchInput<I, P>
+where
+	I: Iterator<Item = RowResult>,
+	P: FnMut(&Headers, &Row) -> bool,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let mut shared = self.shared.borrow_mut();
+		loop {
+			if let Some((position, row)) = shared.queues[self.branch].pop_front() {
+				shared.taken[self.branch] = position;
+				return Some(row);
+			}
+			if !shared.pull() {
+				return None;
+			}
+		}
+	}
+}
+
+/// Merges the output of the two branches of [`Pipeline::branch`](crate::Pipeline::branch). Rows are returned in input order as long as the branches don't buffer or reorder rows.
+pub struct Branch<'a, I, P> {
+	pub shared: Rc<RefCell<BranchShared<I, P>>>,
+	pub branches: [Box<dyn Iterator<Item = RowResult> + 'a>; 2],
+	pub done: [bool; 2],
+	/// A row returned by each branch, along with the input position of the last row the branch read. Rows are held here until the other branch can't return an earlier row.
+	pub pending: [Option<(usize, RowResult)>; 2],
+}
+impl<'a, I, P> Iterator for Branch<'a, I, P>
+where
+	I: Iterator<Item = RowResult>,
+	P: FnMut(&Headers, &Row) -> bool,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let earliest_pending = (0..2)
+				.filter_map(|branch| Some((self.pending[branch].as_ref()?.0, branch)))
+				.min();
+			let branch = match earliest_pending {
+				Some((position, branch)) => {
+					// The other branch may still return a row from earlier in the input, for example if it skipped rows while this one filtered
+					let other = 1 - branch;
+					let other_is_earlier = !self.done[other]
+						&& self.pending[other].is_none()
+						&& (self.shared.borrow().queues[other].front())
+							.is_some_and(|(other_position, _)| *other_position < position);
+					if !other_is_earlier {
+						return self.pending[branch].take().map(|(_, row)| row);
+					}
+					other
+				}
+				None => {
+					// Continue with the branch that has the earliest waiting row
+					let next_branch = {
+						let shared = self.shared.borrow();
+						(0..2)
+							.filter(|branch| !self.done[*branch])
+							.filter_map(|branch| Some((shared.queues[branch].front()?.0, branch)))
+							.min()
+							.map(|(_, branch)| branch)
+					};
+					match next_branch {
+						Some(branch) => branch,
+						None => {
+							if self.shared.borrow_mut().pull() {
+								continue;
+							}
+							// The input is done, so return any rows the branches have buffered
+							(0..2).find(|branch| !self.done[*branch])?
+						}
+					}
+				}
+			};
+			match self.branches[branch].next() {
+				Some(row) => {
+					let position = self.shared.borrow().taken[branch];
+					self.pending[branch] = Some((position, row));
+				}
+				None => self.done[branch] = true,
+			}
+		}
+	}
+}
+
+pub struct FlatMap<I, F: FnMut(&Headers, Row) -> Result<Vec<Row>, Error>> {
+	pub iterator: I,
+	pub f: F,
+	pub source: usize,
+	pub headers: Headers,
+	/// Rows produced by the last call to `f` that haven't been returned yet
+	pub buffer: std::vec::IntoIter<Row>,
+}
+impl<I, F> Iterator for FlatMap<I, F>
+where
+	I: Iterator<Item = RowResult>,
+	F: FnMut(&Headers, Row) -> Result<Vec<Row>, Error>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if let Some(row) = self.buffer.next() {
+				return Some(Ok(row));
+			}
+			let row = match self.iterator.next()? {
+				Ok(row) => row,
+				Err(e) => return Some(Err(e)),
+			};
+			match (self.f)(&self.headers, row) {
+				Ok(rows) => self.buffer = rows.into_iter(),
+				Err(e) => return Some(Err(e.at_source(self.source))),
+			}
+		}
+	}
+}
+
 pub struct MapCol<I, F: FnMut(&str) -> Result<String, Error>> {
 	pub iterator: I,
 	pub f: F,
@@ -122,36 +303,217 @@ where
 			Ok(row) => row,
 			Err(e) => return Some(Err(e)),
 		};
-		let mut row_vec: Vec<_> = row.into_iter().collect();
 		let index = match self.index {
 			Some(index) => index,
 			None => {
 				return Some(Err(
-					Error::MissingColumn(self.name.clone()).at_source(self.source)
+					Error::MissingColumn(self.name.clone()).at_row(self.source, &row)
 				))
 			}
 		};
-		let field = match row_vec.get_mut(index) {
+		let field = match row.get(index) {
 			Some(field) => field,
 			None => {
 				return Some(Err(
-					Error::MissingColumn(self.name.clone()).at_source(self.source)
+					Error::MissingColumn(self.name.clone()).at_row(self.source, &row)
 				))
 			}
 		};
 		let new_value = match (self.f)(field) {
 			Ok(value) => value,
-			Err(e) => return Some(Err(e.at_source(self.source))),
+			Err(e) => return Some(Err(e.at_row(self.source, &row))),
 		};
-		*field = &new_value;
-		Some(Ok(row_vec.into()))
+		Some(Ok(replace_field(&row, index, &new_value)))
 	}
 }
 
-pub struct Filter<I, F: FnMut(&Headers, &Row) -> bool> {
+pub struct MapCols<I, F: FnMut(&str) -> Result<String, Error>> {
+	pub iterator: I,
+	pub f: F,
+	/// Column names and their indexes
+	pub columns: Vec<(String, Option<usize>)>,
+	pub source: usize,
+}
+impl<I, F> Iterator for MapCols<I, F>
+where
+	I: Iterator<Item = RowResult>,
+	F: FnMut(&str) -> Result<String, Error>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let row = match self.iterator.next()? {
+			Ok(row) => row,
+			Err(e) => return Some(Err(e)),
+		};
+		for (col, index) in &self.columns {
+			if index.and_then(|index| row.get(index)).is_none() {
+				return Some(Err(
+					Error::MissingColumn(col.clone()).at_row(self.source, &row)
+				));
+			}
+		}
+		let mut new_row = Row::with_capacity(row.as_slice().len(), row.len());
+		for (i, field) in row.iter().enumerate() {
+			if self.columns.iter().any(|(_, index)| *index == Some(i)) {
+				match (self.f)(field) {
+					Ok(value) => new_row.push_field(&value),
+					Err(e) => return Some(Err(e.at_row(self.source, &row))),
+				}
+			} else {
+				new_row.push_field(field);
+			}
+		}
+		Some(Ok(new_row))
+	}
+}
+
+pub struct UpdateWhere<I, P, F>
+where
+	P: FnMut(&Headers, &Row) -> bool,
+	F: FnMut(&str) -> Result<String, Error>,
+{
 	pub iterator: I,
+	pub predicate: P,
 	pub f: F,
+	pub headers: Headers,
+	pub name: String,
+	pub source: usize,
+	pub index: Option<usize>,
+}
+impl<I, P, F> Iterator for UpdateWhere<I, P, F>
+where
+	I: Iterator<Item = RowResult>,
+	P: FnMut(&Headers, &Row) -> bool,
+	F: FnMut(&str) -> Result<String, Error>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let row = match self.iterator.next()? {
+			Ok(row) => row,
+			Err(e) => return Some(Err(e)),
+		};
+		let (index, field) = match self.index.and_then(|index| Some((index, row.get(index)?))) {
+			Some(indexed_field) => indexed_field,
+			None => {
+				return Some(Err(
+					Error::MissingColumn(self.name.clone()).at_row(self.source, &row)
+				))
+			}
+		};
+		if !(self.predicate)(&self.headers, &row) {
+			return Some(Ok(row));
+		}
+		match (self.f)(field) {
+			Ok(value) => Some(Ok(replace_field(&row, index, &value))),
+			Err(e) => Some(Err(e.at_row(self.source, &row))),
+		}
+	}
+}
+
+pub struct ExpandKv<I> {
+	pub iterator: I,
+	pub name: String,
+	pub index: Option<usize>,
+	pub keys: Vec<String>,
+	pub pair_sep: String,
+	pub kv_sep: String,
+	/// Parse fields as URLs or query strings, decoding percent-encoded keys and values
+	pub query_string: bool,
 	pub source: usize,
+}
+impl<I> Iterator for ExpandKv<I>
+where
+	I: Iterator<Item = RowResult>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let mut row = match self.iterator.next()? {
+			Ok(row) => row,
+			Err(e) => return Some(Err(e)),
+		};
+		let field = match self.index.and_then(|index| row.get(index)) {
+			Some(field) => field,
+			None => {
+				return Some(Err(
+					Error::MissingColumn(self.name.clone()).at_row(self.source, &row)
+				))
+			}
+		};
+		let mut values = vec![String::new(); self.keys.len()];
+		if self.query_string {
+			let query = field.split_once('?').map_or(field, |(_, query)| query);
+			let query = query.split_once('#').map_or(query, |(query, _)| query);
+			for (key, value) in parse_kv(query, &self.pair_sep, &self.kv_sep) {
+				let key = percent_decode(key);
+				if let Some(i) = self.keys.iter().position(|k| *k == key) {
+					values[i] = percent_decode(value);
+				}
+			}
+		} else {
+			for (key, value) in parse_kv(field, &self.pair_sep, &self.kv_sep) {
+				if let Some(i) = self.keys.iter().position(|k| k == key) {
+					values[i] = value.to_string();
+				}
+			}
+		}
+		for value in values {
+			row.push_field(&value);
+		}
+		Some(Ok(row))
+	}
+}
+
+/// Decode `+` and percent-encoded bytes in a query string component. Invalid escapes are kept as they are.
+fn percent_decode(component: &str) -> String {
+	let bytes = component.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		let hex = bytes
+			.get(i + 1..i + 3)
+			.and_then(|hex| std::str::from_utf8(hex).ok())
+			.and_then(|hex| u8::from_str_radix(hex, 16).ok());
+		match (bytes[i], hex) {
+			(b'%', Some(byte)) => {
+				decoded.push(byte);
+				i += 3;
+				continue;
+			}
+			(b'+', _) => decoded.push(b' '),
+			(byte, _) => decoded.push(byte),
+		}
+		i += 1;
+	}
+	String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Split a field like `a=1;b=2` into key-value pairs. Keys without a separator get an empty value.
+pub(crate) fn parse_kv<'f>(
+	field: &'f str,
+	pair_sep: &'f str,
+	kv_sep: &'f str,
+) -> impl Iterator<Item = (&'f str, &'f str)> {
+	field
+		.split(pair_sep)
+		.filter(|pair| !pair.is_empty())
+		.map(move |pair| pair.split_once(kv_sep).unwrap_or((pair, "")))
+}
+
+/// Copy `row`, with the field at `index` replaced by `value`
+pub(crate) fn replace_field(row: &Row, index: usize, value: &str) -> Row {
+	let mut new_row = Row::with_capacity(row.as_slice().len() + value.len(), row.len());
+	for (i, field) in row.iter().enumerate() {
+		new_row.push_field(if i == index { value } else { field });
+	}
+	new_row
+}
+
+pub struct Filter<I, F: FnMut(&Headers, &Row) -> bool> {
+	pub iterator: I,
+	pub f: F,
 	pub headers: Headers,
 }
 impl<I, F> Iterator for Filter<I, F>
@@ -180,7 +542,7 @@ pub struct FilterCol<I, F: FnMut(&str) -> bool> {
 	pub iterator: I,
 	pub f: F,
 	pub source: usize,
-	pub headers: Headers,
+	pub index: Option<usize>,
 }
 impl<I, F> Iterator for FilterCol<I, F>
 where
@@ -195,11 +557,11 @@ where
 				Ok(row) => row,
 				Err(e) => return Some(Err(e)),
 			};
-			let field = match self.headers.get_field(&row, &self.name) {
+			let field = match self.index.and_then(|index| row.get(index)) {
 				Some(field) => field,
 				None => {
 					return Some(Err(
-						Error::MissingColumn(self.name.clone()).at_source(self.source)
+						Error::MissingColumn(self.name.clone()).at_row(self.source, &row)
 					))
 				}
 			};
@@ -211,104 +573,120 @@ where
 	}
 }
 
-pub struct Select<I> {
+/// Filters rows by whether their key appears in another pipeline
+pub struct KeyFilter<'a, I> {
 	pub iterator: I,
-	pub columns: Vec<String>,
+	/// `None` once every key has been read
+	pub other: Option<PipelineIter<'a>>,
+	/// Key column names and their indexes in the other pipeline
+	pub other_columns: Vec<(String, Option<usize>)>,
+	pub other_source: usize,
+	/// Key column names and their indexes
+	pub columns: Vec<(String, Option<usize>)>,
+	pub keys: HashSet<Vec<String>>,
+	/// Keep rows whose key is found, instead of rows whose key isn't found
+	pub keep_matches: bool,
 	pub source: usize,
-	pub headers: Headers,
 }
-impl<I> Iterator for Select<I>
+impl<'a, I> Iterator for KeyFilter<'a, I>
 where
 	I: Iterator<Item = RowResult>,
 {
 	type Item = RowResult;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		let row = match self.iterator.next()? {
-			Ok(row) => row,
-			Err(e) => return Some(Err(e)),
-		};
-		let mut selection = Vec::with_capacity(self.columns.len());
-		for col in &self.columns {
-			let field = match self.headers.get_field(&row, col) {
-				Some(field) => field,
-				None => return Some(Err(Error::MissingColumn(col.clone()).at_source(self.source))),
+		if let Some(other) = &mut self.other {
+			// If any error rows are found, they are returned first
+			for row_result in other.by_ref() {
+				let key = row_result.and_then(|row| {
+					row_key(&row, &self.other_columns).map_err(|e| e.at_source(self.other_source))
+				});
+				match key {
+					Ok(key) => self.keys.insert(key),
+					Err(e) => return Some(Err(e)),
+				};
+			}
+			self.other = None;
+		}
+		loop {
+			let row = match self.iterator.next()? {
+				Ok(row) => row,
+				Err(e) => return Some(Err(e)),
 			};
-			selection.push(field);
+			let key = match row_key(&row, &self.columns) {
+				Ok(key) => key,
+				Err(e) => return Some(Err(e.at_row(self.source, &row))),
+			};
+			if self.keys.contains(&key) == self.keep_matches {
+				return Some(Ok(row));
+			}
 		}
-		Some(Ok(selection.into()))
 	}
 }
 
-pub struct TransformInto<I, F>
-where
-	F: FnMut() -> Vec<Box<dyn Transform>>,
-{
+pub struct DedupConflicts<I> {
 	pub iterator: I,
-	pub groups: LinkedHashMap<u64, Vec<Box<dyn Transform>>>,
-	pub hashers: Vec<Box<dyn Transform>>,
-	pub get_transformers: F,
+	/// Key column names and their indexes
+	pub columns: Vec<(String, Option<usize>)>,
+	/// The first row of each key
+	pub seen: HashMap<Vec<String>, Row>,
 	pub source: usize,
-	pub headers: Headers,
 }
-impl<I, F> Iterator for TransformInto<I, F>
+impl<I> Iterator for DedupConflicts<I>
 where
 	I: Iterator<Item = RowResult>,
-	F: FnMut() -> Vec<Box<dyn Transform>>,
 {
 	type Item = RowResult;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		// If any error rows are found, they are returned first
-		while let Some(row_result) = self.iterator.next() {
-			// First run iterator into LinkedHashMap
-			let row = match row_result {
+		loop {
+			let row = match self.iterator.next()? {
 				Ok(row) => row,
 				Err(e) => return Some(Err(e)),
 			};
-			let hash = match compute_hash(&self.hashers, &self.headers, &row) {
-				Ok(hash) => hash,
-				Err(e) => return Some(Err(e.at_source(self.source))),
+			let key = match row_key(&row, &self.columns) {
+				Ok(key) => key,
+				Err(e) => return Some(Err(e.at_row(self.source, &row))),
 			};
-
-			match self.groups.entry(hash) {
-				Entry::Occupied(_) => {}
-				Entry::Vacant(entry) => {
-					let transformers = (self.get_transformers)();
-					entry.insert(transformers);
+			match self.seen.get(&key) {
+				Some(first) if *first == row => continue,
+				Some(first) => {
+					let error = Error::ConflictingRows(first.clone(), row.clone());
+					return Some(Err(error.at_row(self.source, &row)));
 				}
-			}
-
-			let group_row = self.groups.get_mut(&hash).unwrap();
-			for reducer in group_row {
-				let result = reducer.add_row(&self.headers, &row);
-				if let Err(e) = result {
-					return Some(Err(e.at_source(self.source)));
+				None => {
+					self.seen.insert(key, row.clone());
+					return Some(Ok(row));
 				}
 			}
 		}
-		// Finally, return rows from the LinkedHashMap
-		if let Some(key) = self.groups.keys().next().copied() {
-			let reducers = self.groups.remove(&key).unwrap();
-			let fields: Vec<_> = reducers.iter().map(|reducer| reducer.value()).collect();
-			let row = Row::from(fields);
-			Some(Ok(row))
-		} else {
-			None
+	}
+}
+
+/// Get the fields of the specified columns
+pub(crate) fn row_key(
+	row: &Row,
+	columns: &[(String, Option<usize>)],
+) -> Result<Vec<String>, Error> {
+	let mut key = Vec::with_capacity(columns.len());
+	for (col, index) in columns {
+		match index.and_then(|index| row.get(index)) {
+			Some(field) => key.push(field.to_string()),
+			None => return Err(Error::MissingColumn(col.clone())),
 		}
 	}
+	Ok(key)
 }
 
-pub struct Validate<I, F> {
+pub struct Select<I> {
 	pub iterator: I,
-	pub f: F,
+	/// Column names and their indexes
+	pub columns: Vec<(String, Option<usize>)>,
 	pub source: usize,
-	pub headers: Headers,
 }
-impl<I, F> Iterator for Validate<I, F>
+impl<I> Iterator for Select<I>
 where
 	I: Iterator<Item = RowResult>,
-	F: FnMut(&Headers, &Row) -> Result<(), Error>,
 {
 	type Item = RowResult;
 
@@ -317,20 +695,681 @@ where
 			Ok(row) => row,
 			Err(e) => return Some(Err(e)),
 		};
-		match (self.f)(&self.headers, &row) {
-			Ok(()) => Some(Ok(row)),
-			Err(e) => Some(Err(e.at_source(self.source))),
-		}
-	}
-}
-
-pub struct ValidateCol<I, F> {
-	pub name: String,
-	pub iterator: I,
+		let mut selection = Vec::with_capacity(self.columns.len());
+		for (col, index) in &self.columns {
+			let field = match index.and_then(|index| row.get(index)) {
+				Some(field) => field,
+				None => {
+					return Some(Err(
+						Error::MissingColumn(col.clone()).at_row(self.source, &row)
+					))
+				}
+			};
+			selection.push(field);
+		}
+		Some(Ok(selection.into()))
+	}
+}
+
+pub struct Align<I> {
+	pub iterator: I,
+	/// Column names and their indexes. Columns without an index are filled with empty fields.
+	pub columns: Vec<(String, Option<usize>)>,
+	pub source: usize,
+}
+impl<I> Iterator for Align<I>
+where
+	I: Iterator<Item = RowResult>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let row = match self.iterator.next()? {
+			Ok(row) => row,
+			Err(e) => return Some(Err(e)),
+		};
+		let mut aligned = Row::with_capacity(row.as_slice().len(), self.columns.len());
+		for (col, index) in &self.columns {
+			match index.map(|index| row.get(index)) {
+				Some(Some(field)) => aligned.push_field(field),
+				Some(None) => {
+					return Some(Err(
+						Error::MissingColumn(col.clone()).at_row(self.source, &row)
+					))
+				}
+				None => aligned.push_field(""),
+			}
+		}
+		Some(Ok(aligned))
+	}
+}
+
+pub struct SelectIndices<I> {
+	pub iterator: I,
+	pub indices: Vec<usize>,
+	pub source: usize,
+}
+impl<I> Iterator for SelectIndices<I>
+where
+	I: Iterator<Item = RowResult>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let row = match self.iterator.next()? {
+			Ok(row) => row,
+			Err(e) => return Some(Err(e)),
+		};
+		let mut selection = Vec::with_capacity(self.indices.len());
+		for &index in &self.indices {
+			let field = match row.get(index) {
+				Some(field) => field,
+				None => {
+					let name = format!("at index {} of {} columns", index, row.len());
+					return Some(Err(Error::MissingColumn(name).at_row(self.source, &row)));
+				}
+			};
+			selection.push(field);
+		}
+		Some(Ok(selection.into()))
+	}
+}
+
+pub struct TransformInto<I, F>
+where
+	F: FnMut() -> Vec<Box<dyn Transform>>,
+{
+	pub iterator: I,
+	pub groups: LinkedHashMap<GroupKey, Vec<Box<dyn Transform>>>,
+	pub hashers: Vec<Box<dyn Transform>>,
+	pub get_transformers: F,
+	pub max_groups: Option<usize>,
+	/// `None` to use `DefaultHasher`, which avoids allocating a hasher for each row
+	pub hasher: Option<RowHasher>,
+	pub spill: Option<Spill>,
+	pub source: usize,
+	pub headers: Headers,
+}
+impl<I, F> Iterator for TransformInto<I, F>
+where
+	I: Iterator<Item = RowResult>,
+	F: FnMut() -> Vec<Box<dyn Transform>>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		// If any error rows are found, they are returned first
+		for row_result in self.iterator.by_ref() {
+			// First run iterator into LinkedHashMap
+			let row = match row_result {
+				Ok(row) => row,
+				Err(e) => return Some(Err(e)),
+			};
+			let key = match &self.hasher {
+				Some(RowHasher(build)) => {
+					compute_key(&self.hashers, &mut *build(), &self.headers, &row)
+				}
+				None => compute_key(
+					&self.hashers,
+					&mut DefaultHasher::new(),
+					&self.headers,
+					&row,
+				),
+			};
+			let key = match key {
+				Ok(key) => key,
+				Err(e) => return Some(Err(e.at_row(self.source, &row))),
+			};
+
+			let group_count = self.groups.len();
+			let group_row = match self.groups.entry(key) {
+				Entry::Occupied(entry) => entry.into_mut(),
+				Entry::Vacant(entry) => {
+					if let Some(max_groups) = self.max_groups {
+						if group_count >= max_groups {
+							return Some(Err(
+								Error::TooManyGroups(max_groups).at_source(self.source)
+							));
+						}
+					}
+					let transformers = (self.get_transformers)();
+					entry.insert(transformers)
+				}
+			};
+			for reducer in group_row {
+				let result = reducer.add_row(&self.headers, &row);
+				if let Err(e) = result {
+					return Some(Err(e.at_row(self.source, &row)));
+				}
+			}
+			if let Some(spill) = &mut self.spill {
+				if self.groups.len() > spill.max_groups_in_memory {
+					if let Err(e) = spill.write(&mut self.groups) {
+						return Some(Err(e.at_source(self.source)));
+					}
+				}
+			}
+		}
+		// If groups were spilled to disk, merge them back one file at a time
+		if let Some(spill) = self.spill.as_mut().filter(|spill| spill.spilled) {
+			if !spill.merging {
+				if let Err(e) = spill.write(&mut self.groups) {
+					return Some(Err(e.at_source(self.source)));
+				}
+			}
+			while self.groups.is_empty() {
+				match spill.merge_next(&mut self.groups, &mut self.get_transformers) {
+					Ok(true) => {}
+					Ok(false) => return None,
+					Err(e) => return Some(Err(e.at_source(self.source))),
+				}
+			}
+		}
+		// Finally, return rows from the LinkedHashMap
+		if let Some((_, reducers)) = self.groups.pop_front() {
+			let fields: Vec<_> = reducers.iter().map(|reducer| reducer.value()).collect();
+			let row = Row::from(fields);
+			Some(Ok(row))
+		} else {
+			None
+		}
+	}
+}
+
+/// The key fields and transformers of a group
+type SortedGroup = (Vec<String>, Vec<Box<dyn Transform>>);
+
+pub struct TransformSorted<I, F> {
+	pub iterator: I,
+	pub key_cols: Vec<(String, Option<usize>)>,
+	pub get_transformers: F,
+	/// The key and transformers of the current group
+	pub current: Option<SortedGroup>,
+	/// An error to return after the finished group it interrupted
+	pub pending_error: Option<PlError>,
+	pub source: usize,
+	pub headers: Headers,
+}
+impl<I, F> TransformSorted<I, F>
+where
+	F: FnMut() -> Vec<Box<dyn Transform>>,
+{
+	fn group_row(key: Vec<String>, transformers: Vec<Box<dyn Transform>>) -> Row {
+		let values = transformers.iter().map(|transformer| transformer.value());
+		key.into_iter().chain(values).collect::<Vec<_>>().into()
+	}
+}
+impl<I, F> Iterator for TransformSorted<I, F>
+where
+	I: Iterator<Item = RowResult>,
+	F: FnMut() -> Vec<Box<dyn Transform>>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if let Some(e) = self.pending_error.take() {
+			return Some(Err(e));
+		}
+		loop {
+			let row = match self.iterator.next() {
+				Some(Ok(row)) => row,
+				Some(Err(e)) => return Some(Err(e)),
+				None => {
+					let (key, transformers) = self.current.take()?;
+					return Some(Ok(Self::group_row(key, transformers)));
+				}
+			};
+			let mut key = Vec::with_capacity(self.key_cols.len());
+			for (name, index) in &self.key_cols {
+				match index.and_then(|index| row.get(index)) {
+					Some(field) => key.push(field.to_string()),
+					None => {
+						return Some(Err(
+							Error::MissingColumn(name.clone()).at_row(self.source, &row)
+						))
+					}
+				}
+			}
+			let mut finished = None;
+			if !matches!(&self.current, Some((current_key, _)) if *current_key == key) {
+				finished = self.current.take();
+				self.current = Some((key, (self.get_transformers)()));
+			}
+			let (_, transformers) = self.current.as_mut()?;
+			for transformer in transformers {
+				if let Err(e) = transformer.add_row(&self.headers, &row) {
+					let e = e.at_row(self.source, &row);
+					match finished {
+						Some((key, transformers)) => {
+							self.pending_error = Some(e);
+							return Some(Ok(Self::group_row(key, transformers)));
+						}
+						None => return Some(Err(e)),
+					}
+				}
+			}
+			if let Some((key, transformers)) = finished {
+				return Some(Ok(Self::group_row(key, transformers)));
+			}
+		}
+	}
+}
+
+pub struct Aggregate<'a, I> {
+	pub iterator: I,
+	/// `None` once the summary row has been returned
+	pub transformers: Option<Vec<Box<dyn Transform + 'a>>>,
+	pub source: usize,
+	pub headers: Headers,
+}
+impl<'a, I> Iterator for Aggregate<'a, I>
+where
+	I: Iterator<Item = RowResult>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let transformers = self.transformers.as_mut()?;
+		// If any error rows are found, they are returned first
+		for row_result in self.iterator.by_ref() {
+			let row = match row_result {
+				Ok(row) => row,
+				Err(e) => return Some(Err(e)),
+			};
+			for transformer in transformers.iter_mut() {
+				if let Err(e) = transformer.add_row(&self.headers, &row) {
+					return Some(Err(e.at_row(self.source, &row)));
+				}
+			}
+		}
+		let transformers = self.transformers.take()?;
+		let fields: Vec<_> = transformers.iter().map(|t| t.value()).collect();
+		Some(Ok(Row::from(fields)))
+	}
+}
+
+/// Collects every row, then processes them all at once using `f`
+pub struct Buffered<I, F> {
+	pub iterator: I,
+	/// `None` once the rows have been processed
+	pub f: Option<F>,
+	pub source: usize,
+	pub rows: Vec<Row>,
+	pub output: std::vec::IntoIter<Row>,
+}
+impl<I, F> Buffered<I, F> {
+	pub fn new(iterator: I, f: F, source: usize) -> Self {
+		Self {
+			iterator,
+			f: Some(f),
+			source,
+			rows: Vec::new(),
+			output: Vec::new().into_iter(),
+		}
+	}
+}
+impl<I, F> Iterator for Buffered<I, F>
+where
+	I: Iterator<Item = RowResult>,
+	F: FnOnce(Vec<Row>) -> Result<Vec<Row>, Error>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.f.is_some() {
+			// If any error rows are found, they are returned first
+			for row_result in self.iterator.by_ref() {
+				match row_result {
+					Ok(row) => self.rows.push(row),
+					Err(e) => return Some(Err(e)),
+				}
+			}
+			let f = self.f.take()?;
+			match f(std::mem::take(&mut self.rows)) {
+				Ok(rows) => self.output = rows.into_iter(),
+				Err(e) => return Some(Err(e.at_source(self.source))),
+			}
+		}
+		self.output.next().map(Ok)
+	}
+}
+
+pub struct Lag<I> {
+	pub iterator: I,
+	pub name: String,
+	pub index: Option<usize>,
+	pub offset: usize,
+	/// The fields of the last `offset` rows
+	pub previous: VecDeque<String>,
+	pub source: usize,
+}
+impl<I> Iterator for Lag<I>
+where
+	I: Iterator<Item = RowResult>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let mut row = match self.iterator.next()? {
+			Ok(row) => row,
+			Err(e) => return Some(Err(e)),
+		};
+		let field = match self.index.and_then(|index| row.get(index)) {
+			Some(field) => field,
+			None => {
+				return Some(Err(
+					Error::MissingColumn(self.name.clone()).at_row(self.source, &row)
+				))
+			}
+		};
+		self.previous.push_back(field.to_string());
+		let value = match self.previous.len() > self.offset {
+			true => self.previous.pop_front().unwrap_or_default(),
+			false => String::new(),
+		};
+		row.push_field(&value);
+		Some(Ok(row))
+	}
+}
+
+pub struct MovingAverage<I> {
+	pub iterator: I,
+	pub name: String,
+	pub index: Option<usize>,
+	pub window: usize,
+	/// Whether to average partial windows at the start, instead of leaving them empty
+	pub partial: bool,
+	/// The last `window` values
+	pub values: VecDeque<f64>,
+	pub source: usize,
+}
+impl<I> Iterator for MovingAverage<I>
+where
+	I: Iterator<Item = RowResult>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let mut row = match self.iterator.next()? {
+			Ok(row) => row,
+			Err(e) => return Some(Err(e)),
+		};
+		let field = match self.index.and_then(|index| row.get(index)) {
+			Some(field) => field,
+			None => {
+				return Some(Err(
+					Error::MissingColumn(self.name.clone()).at_row(self.source, &row)
+				))
+			}
+		};
+		let value: f64 = match field.parse() {
+			Ok(value) => value,
+			Err(_) => {
+				return Some(Err(
+					Error::InvalidField(field.to_string()).at_row(self.source, &row)
+				))
+			}
+		};
+		if self.values.len() == self.window {
+			self.values.pop_front();
+		}
+		self.values.push_back(value);
+		if self.values.len() == self.window || self.partial {
+			let mean = self.values.iter().sum::<f64>() / self.values.len() as f64;
+			row.push_field(&mean.to_string());
+		} else {
+			row.push_field("");
+		}
+		Some(Ok(row))
+	}
+}
+
+/// How [`FillGaps`] fills runs of empty fields
+pub enum GapFill {
+	/// Linear interpolation between the values before and after the gap. If `fill_edges` is true, gaps at the start and end are filled with the nearest value.
+	Interpolate { fill_edges: bool },
+	/// The field after the gap
+	BackFill,
+}
+
+/// Fills runs of empty fields once the next non-empty field is known. Only the rows of the current gap are buffered.
+pub struct FillGaps<I> {
+	pub iterator: I,
+	pub name: String,
+	pub index: Option<usize>,
+	pub fill: GapFill,
+	/// The last non-empty field, and its value when interpolating
+	pub previous: Option<(Option<f64>, String)>,
+	/// Rows of the current gap
+	pub gap: VecDeque<RowResult>,
+	/// Rows that are ready to be returned
+	pub ready: VecDeque<RowResult>,
+	pub source: usize,
+}
+impl<I> FillGaps<I> {
+	/// Fill the rows of the current gap now that the next field is known, and move them to `ready`
+	fn close_gap(&mut self, next: Option<(Option<f64>, &str)>) {
+		let index = match self.index {
+			Some(index) => index,
+			None => return self.ready.extend(self.gap.drain(..)),
+		};
+		let steps = self.gap.iter().filter(|row| row.is_ok()).count() + 1;
+		let mut step = 0;
+		for row in self.gap.drain(..) {
+			let row = row.map(|row| {
+				step += 1;
+				let value = match (&self.fill, &self.previous, next) {
+					(GapFill::BackFill, _, Some((_, next))) => next.to_string(),
+					(
+						GapFill::Interpolate { .. },
+						Some((Some(previous), _)),
+						Some((Some(next), _)),
+					) => {
+						let value = previous + (next - previous) * step as f64 / steps as f64;
+						value.to_string()
+					}
+					(GapFill::Interpolate { fill_edges: true }, None, Some((_, next))) => {
+						next.to_string()
+					}
+					(GapFill::Interpolate { fill_edges: true }, Some((_, previous)), None) => {
+						previous.clone()
+					}
+					_ => return row,
+				};
+				replace_field(&row, index, &value)
+			});
+			self.ready.push_back(row);
+		}
+	}
+}
+impl<I> Iterator for FillGaps<I>
+where
+	I: Iterator<Item = RowResult>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if let Some(row) = self.ready.pop_front() {
+				return Some(row);
+			}
+			let row = match self.iterator.next() {
+				Some(Ok(row)) => row,
+				Some(Err(e)) if self.gap.is_empty() => return Some(Err(e)),
+				Some(Err(e)) => {
+					self.gap.push_back(Err(e));
+					continue;
+				}
+				None if self.gap.is_empty() => return None,
+				None => {
+					self.close_gap(None);
+					continue;
+				}
+			};
+			let field = match self.index.and_then(|index| row.get(index)) {
+				Some(field) => field,
+				None => {
+					let error = Error::MissingColumn(self.name.clone()).at_row(self.source, &row);
+					match self.gap.is_empty() {
+						true => return Some(Err(error)),
+						false => self.gap.push_back(Err(error)),
+					}
+					continue;
+				}
+			};
+			if field.is_empty() {
+				self.gap.push_back(Ok(row));
+				continue;
+			}
+			let value = match (&self.fill, field.parse()) {
+				(GapFill::BackFill, _) => None,
+				(GapFill::Interpolate { .. }, Ok(value)) => Some(value),
+				(GapFill::Interpolate { .. }, Err(_)) => {
+					let error = Error::InvalidField(field.to_string()).at_row(self.source, &row);
+					self.gap.push_back(Err(error));
+					continue;
+				}
+			};
+			let field = field.to_string();
+			self.close_gap(Some((value, &field)));
+			self.previous = Some((value, field));
+			self.ready.push_back(Ok(row));
+		}
+	}
+}
+
+pub struct RunningExtreme<I> {
+	pub iterator: I,
+	pub name: String,
+	pub index: Option<usize>,
+	/// `Less` to track the minimum, `Greater` to track the maximum
+	pub keep: Ordering,
+	/// The extreme value so far and its original field
+	pub current: Option<(f64, String)>,
+	pub source: usize,
+}
+impl<I> Iterator for RunningExtreme<I>
+where
+	I: Iterator<Item = RowResult>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let mut row = match self.iterator.next()? {
+			Ok(row) => row,
+			Err(e) => return Some(Err(e)),
+		};
+		let field = match self.index.and_then(|index| row.get(index)) {
+			Some(field) => field,
+			None => {
+				return Some(Err(
+					Error::MissingColumn(self.name.clone()).at_row(self.source, &row)
+				))
+			}
+		};
+		let value: f64 = match field.parse() {
+			Ok(value) => value,
+			Err(_) => {
+				return Some(Err(
+					Error::InvalidField(field.to_string()).at_row(self.source, &row)
+				))
+			}
+		};
+		match &self.current {
+			Some((current, _)) if value.total_cmp(current) != self.keep => {}
+			_ => self.current = Some((value, field.to_string())),
+		}
+		if let Some((_, field)) = &self.current {
+			row.push_field(field);
+		}
+		Some(Ok(row))
+	}
+}
+
+pub struct Lead<I> {
+	pub iterator: I,
+	pub name: String,
+	pub index: Option<usize>,
+	pub offset: usize,
+	/// The current row followed by up to `offset` rows after it. Errors are kept in place so they're returned in order.
+	pub ahead: VecDeque<RowResult>,
+	pub done: bool,
+	pub source: usize,
+}
+impl<I> Iterator for Lead<I>
+where
+	I: Iterator<Item = RowResult>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		// Errors at the front don't need any rows after them
+		while !self.done
+			&& !matches!(self.ahead.front(), Some(Err(_)))
+			&& self.ahead.iter().filter(|row| row.is_ok()).count() <= self.offset
+		{
+			match self.iterator.next() {
+				Some(Ok(row)) => match self.index.and_then(|index| row.get(index)) {
+					Some(_) => self.ahead.push_back(Ok(row)),
+					None => {
+						let error =
+							Error::MissingColumn(self.name.clone()).at_row(self.source, &row);
+						self.ahead.push_back(Err(error));
+					}
+				},
+				Some(Err(e)) => self.ahead.push_back(Err(e)),
+				None => self.done = true,
+			}
+		}
+		let mut row = match self.ahead.pop_front()? {
+			Ok(row) => row,
+			Err(e) => return Some(Err(e)),
+		};
+		let index = self.index?;
+		let value = match self.offset {
+			0 => row.get(index),
+			offset => (self.ahead.iter().filter_map(|row| row.as_ref().ok()))
+				.nth(offset - 1)
+				.and_then(|r| r.get(index)),
+		};
+		let value = value.unwrap_or_default().to_string();
+		row.push_field(&value);
+		Some(Ok(row))
+	}
+}
+
+pub struct Validate<I, F> {
+	pub iterator: I,
 	pub f: F,
 	pub source: usize,
 	pub headers: Headers,
 }
+impl<I, F> Iterator for Validate<I, F>
+where
+	I: Iterator<Item = RowResult>,
+	F: FnMut(&Headers, &Row) -> Result<(), Error>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let row = match self.iterator.next()? {
+			Ok(row) => row,
+			Err(e) => return Some(Err(e)),
+		};
+		match (self.f)(&self.headers, &row) {
+			Ok(()) => Some(Ok(row)),
+			Err(e) => Some(Err(e.at_row(self.source, &row))),
+		}
+	}
+}
+
+pub struct ValidateCol<I, F> {
+	pub name: String,
+	pub iterator: I,
+	pub f: F,
+	pub source: usize,
+	pub index: Option<usize>,
+}
 impl<I, F> Iterator for ValidateCol<I, F>
 where
 	I: Iterator<Item = RowResult>,
@@ -343,17 +1382,17 @@ where
 			Ok(row) => row,
 			Err(e) => return Some(Err(e)),
 		};
-		let field = match self.headers.get_field(&row, &self.name) {
+		let field = match self.index.and_then(|index| row.get(index)) {
 			Some(field) => field,
 			None => {
 				return Some(Err(
-					Error::MissingColumn(self.name.clone()).at_source(self.source)
+					Error::MissingColumn(self.name.clone()).at_row(self.source, &row)
 				))
 			}
 		};
-		match (self.f)(&field) {
+		match (self.f)(field) {
 			Ok(()) => Some(Ok(row)),
-			Err(e) => Some(Err(e.at_source(self.source))),
+			Err(e) => Some(Err(e.at_row(self.source, &row))),
 		}
 	}
 }
@@ -364,6 +1403,7 @@ pub struct Flush<I, T> {
 	pub source: usize,
 	/// `None` if headers have been written, `Some` otherwise
 	headers: Option<Headers>,
+	finished: bool,
 }
 impl<I, T> Flush<I, T> {
 	pub fn new(iterator: I, target: T, source: usize, headers: Headers) -> Self {
@@ -372,6 +1412,7 @@ impl<I, T> Flush<I, T> {
 			target,
 			source,
 			headers: Some(headers),
+			finished: false,
 		}
 	}
 }
@@ -390,14 +1431,87 @@ where
 			}
 		}
 
-		let row = match self.iterator.next()? {
-			Ok(row) => row,
-			Err(e) => return Some(Err(e)),
+		let row = match self.iterator.next() {
+			Some(Ok(row)) => row,
+			Some(Err(e)) => return Some(Err(e)),
+			None if self.finished => return None,
+			None => {
+				self.finished = true;
+				return match self.target.finish() {
+					Ok(()) => None,
+					Err(e) => Some(Err(Error::Csv(e).at_source(self.source))),
+				};
+			}
 		};
-		let r = match self.target.write_row(&row) {
+		match self.target.write_row(&row) {
 			Ok(()) => Some(Ok(row)),
-			Err(e) => return Some(Err(Error::Csv(e).at_source(self.source))),
+			Err(e) => Some(Err(Error::Csv(e).at_row(self.source, &row))),
+		}
+	}
+}
+
+pub struct PartitionBy<'a, I, F> {
+	pub iterator: I,
+	pub name: String,
+	pub index: Option<usize>,
+	pub get_target: F,
+	/// Targets that have had headers written to them, by key
+	pub targets: LinkedHashMap<String, Box<dyn Target + 'a>>,
+	pub headers: Headers,
+	pub finished: bool,
+	pub source: usize,
+}
+impl<'a, I, F> Iterator for PartitionBy<'a, I, F>
+where
+	I: Iterator<Item = RowResult>,
+	F: FnMut(&str) -> Box<dyn Target + 'a>,
+{
+	type Item = RowResult;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let row = match self.iterator.next() {
+			Some(Ok(row)) => row,
+			Some(Err(e)) => return Some(Err(e)),
+			None if self.finished => return None,
+			None => {
+				self.finished = true;
+				// Finish every target even if one fails, then return the first error
+				let mut first_error = None;
+				for (_, target) in self.targets.iter_mut() {
+					if let Err(e) = target.finish() {
+						first_error.get_or_insert(e);
+					}
+				}
+				return first_error.map(|e| Err(Error::Csv(e).at_source(self.source)));
+			}
+		};
+		let key = match self.index.and_then(|index| row.get(index)) {
+			Some(key) => key,
+			None => {
+				return Some(Err(
+					Error::MissingColumn(self.name.clone()).at_row(self.source, &row)
+				))
+			}
 		};
-		r
+		if !self.targets.contains_key(key) {
+			let mut target = (self.get_target)(key);
+			if let Err(e) = target.write_headers(&self.headers) {
+				return Some(Err(Error::Csv(e).at_source(self.source)));
+			}
+			self.targets.insert(key.to_string(), target);
+		}
+		let target = self.targets.get_mut(key).unwrap();
+		match target.write_row(&row) {
+			Ok(()) => Some(Ok(row)),
+			Err(e) => Some(Err(Error::Csv(e).at_row(self.source, &row))),
+		}
 	}
 }
+
+#[test]
+fn test_percent_decode() {
+	assert_eq!(percent_decode("a%20b+c"), "a b c");
+	assert_eq!(percent_decode("%C3%B8"), "ø");
+	assert_eq!(percent_decode("100%"), "100%");
+	assert_eq!(percent_decode("%zz"), "%zz");
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+use std::io;
+
+/// Fails with a [`RowSizeExceeded`] error when a CSV row is longer than the maximum number of bytes, so huge rows are never read fully into memory. Newlines inside quoted fields don't end a row.
+pub(crate) struct RowSizeLimit<R> {
+	inner: R,
+	max_row_size: usize,
+	delimiter: u8,
+	/// Bytes of the current row that have been read
+	row_size: usize,
+	quoting: Quoting,
+	exceeded: bool,
+}
+impl<R: io::Read> RowSizeLimit<R> {
+	pub fn new(inner: R, max_row_size: usize, delimiter: u8) -> Self {
+		Self {
+			inner,
+			max_row_size,
+			delimiter,
+			row_size: 0,
+			quoting: Quoting::FieldStart,
+			exceeded: false,
+		}
+	}
+}
+
+/// Where the last byte was within a field. A quote only opens a quoted field at the start of the field, so a field like `5" pipe` is unquoted.
+#[derive(Clone, Copy)]
+enum Quoting {
+	FieldStart,
+	Unquoted,
+	Quoted,
+	/// A quote inside a quoted field, which either ends it or is escaped by another quote
+	QuoteInQuoted,
+}
+impl<R: io::Read> io::Read for RowSizeLimit<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		if self.exceeded {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, RowSizeExceeded));
+		}
+		let len = self.inner.read(buf)?;
+		for (i, byte) in buf[..len].iter().enumerate() {
+			self.row_size += 1;
+			if self.row_size > self.max_row_size {
+				self.exceeded = true;
+				// Return the rows before this one first
+				return match i {
+					0 => Err(io::Error::new(io::ErrorKind::InvalidData, RowSizeExceeded)),
+					i => Ok(i),
+				};
+			}
+			self.quoting = match (self.quoting, *byte) {
+				(Quoting::FieldStart | Quoting::QuoteInQuoted, b'"') => Quoting::Quoted,
+				(Quoting::Quoted, b'"') => Quoting::QuoteInQuoted,
+				(Quoting::Quoted, _) => Quoting::Quoted,
+				(_, b'\n') => {
+					self.row_size = 0;
+					Quoting::FieldStart
+				}
+				(_, byte) if byte == self.delimiter => Quoting::FieldStart,
+				_ => Quoting::Unquoted,
+			};
+		}
+		Ok(len)
+	}
+}
+
+/// Returned by [`RowSizeLimit`] inside an [`io::Error`]
+#[derive(Debug)]
+pub(crate) struct RowSizeExceeded;
+impl fmt::Display for RowSizeExceeded {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Row is too large")
+	}
+}
+impl std::error::Error for RowSizeExceeded {}
+
+/// Check if a CSV error was caused by a [`RowSizeLimit`]
+pub(crate) fn is_row_size_exceeded(err: &csv::Error) -> bool {
+	match err.kind() {
+		csv::ErrorKind::Io(err) => err
+			.get_ref()
+			.is_some_and(|inner| inner.is::<RowSizeExceeded>()),
+		_ => false,
+	}
+}
+
+#[test]
+fn test_row_size_limit() {
+	use std::io::Read;
+
+	let mut output = String::new();
+	let source = "A,B\n\"1\n2\",3\n";
+	RowSizeLimit::new(source.as_bytes(), 8, b',')
+		.read_to_string(&mut output)
+		.unwrap();
+	assert_eq!(output, source);
+
+	let mut reader = RowSizeLimit::new(source.as_bytes(), 7, b',');
+	let mut buf = [0; 64];
+	assert_eq!(reader.read(&mut buf).unwrap(), 11);
+	assert!(reader.read(&mut buf).is_err());
+}
+
+#[test]
+fn row_size_limit_quote_inside_field() {
+	use std::io::Read;
+
+	let mut output = String::new();
+	let source = "A,B\n5\" pipe,3\n\"a\"\"\nb\",4\n";
+	RowSizeLimit::new(source.as_bytes(), 12, b',')
+		.read_to_string(&mut output)
+		.unwrap();
+	assert_eq!(output, source);
+}
@@ -0,0 +1,138 @@
+use crate::Error;
+use std::cmp::Ordering;
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+	/// Smallest first
+	Asc,
+	/// Largest first
+	Desc,
+}
+impl Order {
+	/// Apply the direction to an ascending ordering
+	pub fn apply(self, ordering: Ordering) -> Ordering {
+		match self {
+			Order::Asc => ordering,
+			Order::Desc => ordering.reverse(),
+		}
+	}
+}
+
+/// How fields are compared when sorting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+	/// Compare fields as text
+	Text,
+	/// Parse fields as `f64` and compare them as numbers
+	Numeric,
+	/// Compare fields as text, but compare runs of digits as numbers, so `file2` comes before `file10`
+	Natural,
+}
+
+/// A field prepared for comparing with [`SortKey`]
+pub(crate) enum SortValue {
+	Text(String),
+	Numeric(f64),
+	Natural(String),
+}
+impl SortValue {
+	pub fn new(key: SortKey, field: &str) -> Result<Self, Error> {
+		Ok(match key {
+			SortKey::Text => SortValue::Text(field.to_string()),
+			SortKey::Numeric => match field.parse() {
+				Ok(n) => SortValue::Numeric(n),
+				Err(_) => return Err(Error::InvalidField(field.to_string())),
+			},
+			SortKey::Natural => SortValue::Natural(field.to_string()),
+		})
+	}
+	pub fn compare(&self, other: &Self) -> Ordering {
+		match (self, other) {
+			(SortValue::Text(a), SortValue::Text(b)) => a.cmp(b),
+			(SortValue::Numeric(a), SortValue::Numeric(b)) => a.total_cmp(b),
+			(SortValue::Natural(a), SortValue::Natural(b)) => natural_cmp(a, b),
+			_ => Ordering::Equal,
+		}
+	}
+}
+
+/// Compare strings, treating runs of digits as numbers
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+	let mut a = a.chars().peekable();
+	let mut b = b.chars().peekable();
+	loop {
+		match (a.peek(), b.peek()) {
+			(None, None) => return Ordering::Equal,
+			(None, Some(_)) => return Ordering::Less,
+			(Some(_), None) => return Ordering::Greater,
+			(Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+				let x = take_digits(&mut a);
+				let y = take_digits(&mut b);
+				let x_trimmed = x.trim_start_matches('0');
+				let y_trimmed = y.trim_start_matches('0');
+				let ordering = x_trimmed
+					.len()
+					.cmp(&y_trimmed.len())
+					.then_with(|| x_trimmed.cmp(y_trimmed))
+					.then_with(|| x.len().cmp(&y.len()));
+				if ordering != Ordering::Equal {
+					return ordering;
+				}
+			}
+			(Some(x), Some(y)) => {
+				let ordering = x.cmp(y);
+				if ordering != Ordering::Equal {
+					return ordering;
+				}
+				a.next();
+				b.next();
+			}
+		}
+	}
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+	let mut digits = String::new();
+	while let Some(c) = chars.next_if(char::is_ascii_digit) {
+		digits.push(c);
+	}
+	digits
+}
+
+/// Rank each value, starting at 1. Equal values share a rank. With `dense`, ranks have no gaps (1, 1, 2), otherwise they skip ahead (1, 1, 3).
+pub(crate) fn rank(values: &[f64], order: Order, dense: bool) -> Vec<usize> {
+	let mut indexes: Vec<usize> = (0..values.len()).collect();
+	indexes.sort_by(|a, b| order.apply(values[*a].total_cmp(&values[*b])));
+	let mut ranks = vec![0; values.len()];
+	let mut previous: Option<(f64, usize)> = None;
+	for (position, &index) in indexes.iter().enumerate() {
+		let value = values[index];
+		let rank = match previous {
+			Some((prev_value, prev_rank)) if prev_value == value => prev_rank,
+			Some((_, prev_rank)) if dense => prev_rank + 1,
+			_ => position + 1,
+		};
+		ranks[index] = rank;
+		previous = Some((value, rank));
+	}
+	ranks
+}
+
+#[test]
+fn test_rank() {
+	let values = [5.0, 9.0, 5.0, 1.0];
+	assert_eq!(rank(&values, Order::Desc, false), vec![2, 1, 2, 4]);
+	assert_eq!(rank(&values, Order::Desc, true), vec![2, 1, 2, 3]);
+	assert_eq!(rank(&values, Order::Asc, false), vec![2, 4, 2, 1]);
+}
+
+#[test]
+fn test_natural_cmp() {
+	let mut names = vec!["file10", "file2", "file1b", "file02", "File3", "file"];
+	names.sort_by(|a, b| natural_cmp(a, b));
+	assert_eq!(
+		names,
+		vec!["File3", "file", "file1b", "file2", "file02", "file10"]
+	);
+}
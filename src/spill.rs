@@ -0,0 +1,207 @@
+use crate::transform::{GroupKey, Transform};
+use crate::{Error, Row};
+use csv::{ReaderBuilder, Writer};
+use linked_hash_map::LinkedHashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of files that spilled groups are divided into. Merging happens one file at a time, so only the groups of one file need to fit in memory.
+const PARTITIONS: u64 = 64;
+
+/// Used to give each spill a unique directory
+static SPILL_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) type Groups = LinkedHashMap<GroupKey, Vec<Box<dyn Transform>>>;
+
+/// Writes group states to files, and merges them back one file at a time
+pub(crate) struct Spill {
+	dir: PathBuf,
+	pub max_groups_in_memory: usize,
+	writers: Vec<Option<Writer<File>>>,
+	/// Whether any groups have been written to disk
+	pub spilled: bool,
+	/// Whether merging has started, after which no more groups are written
+	pub merging: bool,
+	next_partition: u64,
+}
+impl Spill {
+	pub fn new(parent_dir: PathBuf, max_groups_in_memory: usize) -> Self {
+		let dir_name = format!(
+			"csv-pipeline-spill-{}-{}",
+			std::process::id(),
+			SPILL_COUNT.fetch_add(1, Ordering::Relaxed),
+		);
+		Self {
+			dir: parent_dir.join(dir_name),
+			max_groups_in_memory,
+			writers: (0..PARTITIONS).map(|_| None).collect(),
+			spilled: false,
+			merging: false,
+			next_partition: 0,
+		}
+	}
+
+	fn partition_path(&self, partition: u64) -> PathBuf {
+		self.dir.join(format!("{}.csv", partition))
+	}
+
+	/// Move every group from memory to disk
+	pub fn write(&mut self, groups: &mut Groups) -> Result<(), Error> {
+		if !self.spilled {
+			fs::create_dir_all(&self.dir).map_err(|e| Error::Csv(e.into()))?;
+			self.spilled = true;
+		}
+		while let Some(((hash, key), transformers)) = groups.pop_front() {
+			let mut record = vec![hash.to_string(), key.len().to_string()];
+			record.extend(key);
+			for transformer in &transformers {
+				match transformer.state() {
+					Some(state) => record.push(state),
+					None => return Err(Error::NotMergeable(transformer.name())),
+				}
+			}
+			let partition = hash % PARTITIONS;
+			let path = self.partition_path(partition);
+			let writer = match &mut self.writers[partition as usize] {
+				Some(writer) => writer,
+				writer => writer.insert(Writer::from_path(path).map_err(Error::Csv)?),
+			};
+			writer.write_record(&record).map_err(Error::Csv)?;
+		}
+		Ok(())
+	}
+
+	/// Read and merge the groups of the next file into `groups`. Returns false when there are no files left.
+	pub fn merge_next(
+		&mut self,
+		groups: &mut Groups,
+		get_transformers: &mut dyn FnMut() -> Vec<Box<dyn Transform>>,
+	) -> Result<bool, Error> {
+		if !self.merging {
+			self.merging = true;
+			for mut writer in self.writers.iter_mut().filter_map(Option::take) {
+				writer.flush().map_err(|e| Error::Csv(e.into()))?;
+			}
+		}
+		while self.next_partition < PARTITIONS {
+			let path = self.partition_path(self.next_partition);
+			self.next_partition += 1;
+			if !path.exists() {
+				continue;
+			}
+			let reader = ReaderBuilder::new()
+				.has_headers(false)
+				.flexible(true)
+				.from_path(&path)
+				.map_err(Error::Csv)?;
+			for record in reader.into_records() {
+				let record = record.map_err(Error::Csv)?;
+				let (key, states) = parse_record(&record)?;
+				let transformers = groups.entry(key).or_insert_with(&mut *get_transformers);
+				for (transformer, state) in transformers.iter_mut().zip(states) {
+					transformer.merge(state)?;
+				}
+			}
+			fs::remove_file(&path).map_err(|e| Error::Csv(e.into()))?;
+			return Ok(true);
+		}
+		Ok(false)
+	}
+}
+impl Drop for Spill {
+	fn drop(&mut self) {
+		if self.spilled {
+			for writer in self.writers.iter_mut() {
+				writer.take();
+			}
+			let _ = fs::remove_dir_all(&self.dir);
+		}
+	}
+}
+
+fn parse_record(record: &Row) -> Result<(GroupKey, Vec<&str>), Error> {
+	let invalid = || {
+		let msg = "Invalid spill file";
+		Error::Csv(io::Error::new(io::ErrorKind::InvalidData, msg).into())
+	};
+	let mut fields = record.iter();
+	let hash = fields
+		.next()
+		.and_then(|f| f.parse().ok())
+		.ok_or_else(invalid)?;
+	let key_len: usize = fields
+		.next()
+		.and_then(|f| f.parse().ok())
+		.ok_or_else(invalid)?;
+	let key: Vec<String> = fields.by_ref().take(key_len).map(String::from).collect();
+	if key.len() != key_len {
+		return Err(invalid());
+	}
+	Ok(((hash, key), fields.collect()))
+}
+
+#[test]
+fn test_spill() {
+	use crate::{Pipeline, TransformOptions, Transformer};
+
+	let source = "\
+		Name,Score\n\
+		A,1\n\
+		B,2\n\
+		A,3\n\
+		C,4\n\
+		B,5\n";
+	let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+		.unwrap()
+		.transform_into_with(
+			|| {
+				vec![
+					Transformer::new("Name").keep_unique(),
+					Transformer::new("Score").sum(0),
+					Transformer::new("Count").count(),
+				]
+			},
+			TransformOptions::new().spill_to_disk("target/test_spill", 1),
+		)
+		.collect_into_rows()
+		.unwrap();
+	let mut rows: Vec<Vec<&str>> = csv.iter().map(|row| row.iter().collect()).collect();
+	rows[1..].sort();
+	assert_eq!(
+		rows,
+		vec![
+			vec!["Name", "Score", "Count"],
+			vec!["A", "4", "2"],
+			vec!["B", "7", "2"],
+			vec!["C", "4", "1"],
+		]
+	);
+	assert_eq!(fs::read_dir("target/test_spill").unwrap().count(), 0);
+}
+
+#[test]
+fn spill_sum_init() {
+	use crate::{Pipeline, TransformOptions, Transformer};
+
+	let csv = Pipeline::from_str("Name,Score\nA,1\nB,2\nA,1\n")
+		.unwrap()
+		.transform_into_with(
+			|| {
+				vec![
+					Transformer::new("Name").keep_unique(),
+					Transformer::new("Score").sum(100),
+				]
+			},
+			TransformOptions::new().spill_to_disk("target/test_spill_sum_init", 1),
+		)
+		.collect_into_rows()
+		.unwrap();
+	let mut rows: Vec<Vec<&str>> = csv.iter().map(|row| row.iter().collect()).collect();
+	rows[1..].sort();
+	assert_eq!(
+		rows,
+		vec![vec!["Name", "Score"], vec!["A", "102"], vec!["B", "102"],]
+	);
+}
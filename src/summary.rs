@@ -0,0 +1,86 @@
+use crate::{Headers, Row};
+
+/// Statistics for each column of a pipeline, which you can get using [`Pipeline::describe`](crate::Pipeline::describe).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+	pub columns: Vec<ColumnSummary>,
+}
+impl Summary {
+	pub(crate) fn new(headers: &Headers) -> Self {
+		Self {
+			columns: headers
+				.into_iter()
+				.map(|name| ColumnSummary {
+					name: name.to_string(),
+					count: 0,
+					non_empty: 0,
+					numeric: None,
+					is_numeric: true,
+				})
+				.collect(),
+		}
+	}
+
+	pub(crate) fn add_row(&mut self, row: &Row) {
+		for (column, field) in self.columns.iter_mut().zip(row) {
+			column.add_field(field);
+		}
+	}
+
+	/// Get the summary of a column by its name
+	pub fn get(&self, name: &str) -> Option<&ColumnSummary> {
+		self.columns.iter().find(|column| column.name == name)
+	}
+}
+
+/// Statistics for a single column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSummary {
+	pub name: String,
+	/// Number of fields
+	pub count: usize,
+	/// Number of fields that aren't empty
+	pub non_empty: usize,
+	/// Only set if every non-empty field is a number, and there is at least one
+	pub numeric: Option<NumericSummary>,
+	is_numeric: bool,
+}
+impl ColumnSummary {
+	fn add_field(&mut self, field: &str) {
+		self.count += 1;
+		if field.is_empty() {
+			return;
+		}
+		self.non_empty += 1;
+		if !self.is_numeric {
+			return;
+		}
+		let value: f64 = match field.parse() {
+			Ok(value) => value,
+			Err(_) => {
+				self.is_numeric = false;
+				self.numeric = None;
+				return;
+			}
+		};
+		let numeric = self.numeric.get_or_insert(NumericSummary {
+			min: value,
+			max: value,
+			mean: 0.0,
+			sum: 0.0,
+		});
+		numeric.min = numeric.min.min(value);
+		numeric.max = numeric.max.max(value);
+		numeric.sum += value;
+		numeric.mean = numeric.sum / self.non_empty as f64;
+	}
+}
+
+/// Statistics for a numeric column. Empty fields are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericSummary {
+	pub min: f64,
+	pub max: f64,
+	pub mean: f64,
+	pub sum: f64,
+}
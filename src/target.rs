@@ -1,6 +1,7 @@
 use crate::{Headers, Row};
 use csv::WriterBuilder;
-use std::fs::{self, File};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
 use std::io;
 use std::path::PathBuf;
 
@@ -8,19 +9,80 @@ pub trait Target {
 	/// Useful for initializations
 	fn write_headers(&mut self, headers: &Headers) -> Result<(), csv::Error>;
 	fn write_row(&mut self, row: &Row) -> Result<(), csv::Error>;
+	/// Called after the last row has been written. Useful for flushing or writing buffered rows
+	fn finish(&mut self) -> Result<(), csv::Error> {
+		Ok(())
+	}
+}
+
+/// Write a row, replacing empty fields with the null token if there is one
+fn write_record<W: io::Write>(
+	writer: &mut csv::Writer<W>,
+	row: &Row,
+	null_token: &Option<String>,
+) -> Result<(), csv::Error> {
+	match null_token {
+		Some(token) => {
+			writer.write_record(
+				row.iter()
+					.map(|field| if field.is_empty() { token } else { field }),
+			)
+		}
+		None => writer.write_record(row),
+	}
 }
 
 pub struct PathTarget {
 	path: PathBuf,
 	writer: Option<csv::Writer<File>>,
+	null_token: Option<String>,
+	write_headers: bool,
+	append: bool,
 }
 impl PathTarget {
 	pub fn new<P: Into<PathBuf>>(path: P) -> Self {
 		Self {
 			path: path.into(),
 			writer: None,
+			null_token: None,
+			write_headers: true,
+			append: false,
 		}
 	}
+	/// Write empty fields as this token, for example `\N` for Postgres `COPY`
+	pub fn null_token(mut self, token: &str) -> Self {
+		self.null_token = Some(token.to_string());
+		self
+	}
+	/// Set whether to write the header row. Defaults to `true`.
+	pub fn with_headers(mut self, write_headers: bool) -> Self {
+		self.write_headers = write_headers;
+		self
+	}
+	/// Add the rows to the end of the file instead of replacing it. Together with [`with_headers`](PathTarget::with_headers), this lets you build one CSV file from multiple pipeline runs, where only the first one writes the header row.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Target};
+	///
+	/// let path = std::env::temp_dir().join("csv-pipeline-append-example.csv");
+	/// for (i, source) in ["A,B\n1,2\n", "A,B\n3,4\n"].into_iter().enumerate() {
+	///   let first = i == 0;
+	///   Pipeline::from_str(source)
+	///     .unwrap()
+	///     .flush(Target::path(&path).append(!first).with_headers(first))
+	///     .run()
+	///     .unwrap();
+	/// }
+	///
+	/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "A,B\n1,2\n3,4\n");
+	/// std::fs::remove_file(&path).unwrap();
+	/// ```
+	pub fn append(mut self, append: bool) -> Self {
+		self.append = append;
+		self
+	}
 }
 impl Target for PathTarget {
 	fn write_headers(&mut self, headers: &Headers) -> Result<(), csv::Error> {
@@ -28,52 +90,126 @@ impl Target for PathTarget {
 			fs::create_dir_all(parent)?;
 		}
 
-		self.writer = Some(csv::Writer::from_path(&self.path)?);
-		self.write_row(headers.get_row())
+		let file = if self.append {
+			OpenOptions::new()
+				.create(true)
+				.append(true)
+				.open(&self.path)?
+		} else {
+			File::create(&self.path)?
+		};
+		let mut writer = csv::Writer::from_writer(file);
+		if self.write_headers {
+			writer.write_record(headers.get_row())?;
+		}
+		self.writer = Some(writer);
+		Ok(())
 	}
 	fn write_row(&mut self, row: &Row) -> Result<(), csv::Error> {
-		self.writer.as_mut().unwrap().write_record(row)?;
+		write_record(self.writer.as_mut().unwrap(), row, &self.null_token)
+	}
+	fn finish(&mut self) -> Result<(), csv::Error> {
+		if let Some(writer) = self.writer.as_mut() {
+			writer.flush()?;
+		}
 		Ok(())
 	}
 }
 
 pub struct StdoutTarget {
 	writer: Option<csv::Writer<io::Stdout>>,
+	null_token: Option<String>,
+	write_headers: bool,
 }
 impl StdoutTarget {
 	pub fn new() -> Self {
-		Self { writer: None }
+		Self {
+			writer: None,
+			null_token: None,
+			write_headers: true,
+		}
+	}
+	/// Write empty fields as this token, for example `\N` for Postgres `COPY`
+	pub fn null_token(mut self, token: &str) -> Self {
+		self.null_token = Some(token.to_string());
+		self
+	}
+	/// Set whether to write the header row. Defaults to `true`.
+	pub fn with_headers(mut self, write_headers: bool) -> Self {
+		self.write_headers = write_headers;
+		self
+	}
+}
+impl Default for StdoutTarget {
+	fn default() -> Self {
+		Self::new()
 	}
 }
 impl Target for StdoutTarget {
 	fn write_headers(&mut self, headers: &Headers) -> Result<(), csv::Error> {
-		let writer = WriterBuilder::new().from_writer(io::stdout());
+		let mut writer = WriterBuilder::new().from_writer(io::stdout());
+		if self.write_headers {
+			writer.write_record(headers.get_row())?;
+		}
 		self.writer = Some(writer);
-		self.write_row(headers.get_row())?;
 		Ok(())
 	}
 	fn write_row(&mut self, row: &Row) -> Result<(), csv::Error> {
-		self.writer.as_mut().unwrap().write_record(row)?;
+		write_record(self.writer.as_mut().unwrap(), row, &self.null_token)
+	}
+	fn finish(&mut self) -> Result<(), csv::Error> {
+		if let Some(writer) = self.writer.as_mut() {
+			writer.flush()?;
+		}
 		Ok(())
 	}
 }
 
 pub struct StderrTarget {
 	writer: Option<csv::Writer<io::Stderr>>,
+	null_token: Option<String>,
+	write_headers: bool,
 }
 impl StderrTarget {
 	pub fn new() -> Self {
-		Self { writer: None }
+		Self {
+			writer: None,
+			null_token: None,
+			write_headers: true,
+		}
+	}
+	/// Write empty fields as this token, for example `\N` for Postgres `COPY`
+	pub fn null_token(mut self, token: &str) -> Self {
+		self.null_token = Some(token.to_string());
+		self
+	}
+	/// Set whether to write the header row. Defaults to `true`.
+	pub fn with_headers(mut self, write_headers: bool) -> Self {
+		self.write_headers = write_headers;
+		self
+	}
+}
+impl Default for StderrTarget {
+	fn default() -> Self {
+		Self::new()
 	}
 }
 impl Target for StderrTarget {
 	fn write_headers(&mut self, headers: &Headers) -> Result<(), csv::Error> {
-		let writer = WriterBuilder::new().from_writer(io::stderr());
+		let mut writer = WriterBuilder::new().from_writer(io::stderr());
+		if self.write_headers {
+			writer.write_record(headers.get_row())?;
+		}
 		self.writer = Some(writer);
-		self.write_row(headers.get_row())
+		Ok(())
 	}
 	fn write_row(&mut self, row: &Row) -> Result<(), csv::Error> {
-		self.writer.as_mut().unwrap().write_record(row)?;
+		write_record(self.writer.as_mut().unwrap(), row, &self.null_token)
+	}
+	fn finish(&mut self) -> Result<(), csv::Error> {
+		if let Some(writer) = self.writer.as_mut() {
+			writer.flush()?;
+		}
 		Ok(())
 	}
 }
@@ -98,19 +234,407 @@ impl<'a> io::Write for StringWriter<'a> {
 
 pub struct StringTarget<'a> {
 	writer: csv::Writer<StringWriter<'a>>,
+	null_token: Option<String>,
+	write_headers: bool,
 }
 impl<'a> StringTarget<'a> {
 	pub fn new(s: &'a mut String) -> Self {
 		let writer = WriterBuilder::new().from_writer(StringWriter { s });
-		Self { writer }
+		Self {
+			writer,
+			null_token: None,
+			write_headers: true,
+		}
+	}
+	/// Write empty fields as this token, for example `\N` for Postgres `COPY`
+	pub fn null_token(mut self, token: &str) -> Self {
+		self.null_token = Some(token.to_string());
+		self
+	}
+	/// Set whether to write the header row. Defaults to `true`. This is useful for adding the rows of multiple pipelines to the same string.
+	pub fn with_headers(mut self, write_headers: bool) -> Self {
+		self.write_headers = write_headers;
+		self
 	}
 }
 impl<'a> Target for StringTarget<'a> {
 	fn write_headers(&mut self, headers: &Headers) -> Result<(), csv::Error> {
-		self.write_row(headers.get_row())
+		if !self.write_headers {
+			return Ok(());
+		}
+		self.writer.write_record(headers.get_row())
+	}
+	fn write_row(&mut self, row: &Row) -> Result<(), csv::Error> {
+		write_record(&mut self.writer, row, &self.null_token)
+	}
+	fn finish(&mut self) -> Result<(), csv::Error> {
+		self.writer.flush()?;
+		Ok(())
+	}
+}
+
+/// Writes columns padded with spaces to fixed widths, without delimiters.
+///
+/// By default, the widths are computed from the longest field in each column, which means every row is kept in memory until the pipeline finishes. Use [`widths`](FixedWidthTarget::widths) to set the widths explicitly, which lets rows be written as they come. In that case, fields that are wider than their column result in an error.
+pub struct FixedWidthTarget<W: io::Write> {
+	writer: W,
+	widths: Option<BTreeMap<String, usize>>,
+	/// Column widths in order, once the headers are known
+	column_widths: Vec<usize>,
+	/// The header and rows, when computing the widths
+	rows: Vec<Row>,
+}
+impl<W: io::Write> FixedWidthTarget<W> {
+	pub fn new(writer: W) -> Self {
+		Self {
+			writer,
+			widths: None,
+			column_widths: Vec::new(),
+			rows: Vec::new(),
+		}
+	}
+	/// Use explicit column widths, specified by column name. Every column must have a width.
+	pub fn widths(mut self, widths: Vec<(&str, usize)>) -> Self {
+		let widths = widths
+			.into_iter()
+			.map(|(name, width)| (name.to_string(), width));
+		self.widths = Some(widths.collect());
+		self
+	}
+	fn write_padded(&mut self, row: &Row) -> Result<(), csv::Error> {
+		let mut line = String::new();
+		for (field, &width) in row.iter().zip(&self.column_widths) {
+			let len = field.chars().count();
+			if len > width {
+				let msg = format!("Field \"{}\" is wider than {} characters", field, width);
+				return Err(io::Error::new(io::ErrorKind::InvalidData, msg).into());
+			}
+			line.push_str(&format!("{:<width$}", field, width = width));
+		}
+		line.push('\n');
+		self.writer.write_all(line.as_bytes())?;
+		Ok(())
+	}
+}
+impl<W: io::Write> Target for FixedWidthTarget<W> {
+	fn write_headers(&mut self, headers: &Headers) -> Result<(), csv::Error> {
+		match &self.widths {
+			Some(widths) => {
+				let mut column_widths = Vec::new();
+				for name in headers {
+					match widths.get(name) {
+						Some(width) => column_widths.push(*width),
+						None => {
+							let msg = format!("No width specified for column \"{}\"", name);
+							return Err(io::Error::new(io::ErrorKind::InvalidInput, msg).into());
+						}
+					}
+				}
+				self.column_widths = column_widths;
+				self.write_padded(headers.get_row())
+			}
+			None => {
+				self.rows.push(headers.get_row().clone());
+				Ok(())
+			}
+		}
 	}
 	fn write_row(&mut self, row: &Row) -> Result<(), csv::Error> {
-		self.writer.write_record(row)?;
+		match self.widths {
+			Some(_) => self.write_padded(row),
+			None => {
+				self.rows.push(row.clone());
+				Ok(())
+			}
+		}
+	}
+	fn finish(&mut self) -> Result<(), csv::Error> {
+		if self.widths.is_none() {
+			let rows = std::mem::take(&mut self.rows);
+			self.column_widths = column_widths(&rows);
+			for row in &rows {
+				self.write_padded(row)?;
+			}
+		}
+		self.writer.flush()?;
 		Ok(())
 	}
 }
+
+/// Writes a GitHub-flavored Markdown table. `|` characters are escaped, and newlines are written as `<br>`.
+pub struct MarkdownTarget<W: io::Write> {
+	writer: W,
+}
+impl<W: io::Write> MarkdownTarget<W> {
+	pub fn new(writer: W) -> Self {
+		Self { writer }
+	}
+	fn write_line<'a>(&mut self, fields: impl Iterator<Item = &'a str>) -> io::Result<()> {
+		let mut line = String::from("|");
+		for field in fields {
+			line.push(' ');
+			line.push_str(&field.replace('|', "\\|").replace('\n', "<br>"));
+			line.push_str(" |");
+		}
+		line.push('\n');
+		self.writer.write_all(line.as_bytes())
+	}
+}
+impl<W: io::Write> Target for MarkdownTarget<W> {
+	fn write_headers(&mut self, headers: &Headers) -> Result<(), csv::Error> {
+		self.write_line(headers.into_iter())?;
+		self.write_line(headers.into_iter().map(|_| "---"))?;
+		Ok(())
+	}
+	fn write_row(&mut self, row: &Row) -> Result<(), csv::Error> {
+		self.write_line(row.iter())?;
+		Ok(())
+	}
+	fn finish(&mut self) -> Result<(), csv::Error> {
+		self.writer.flush()?;
+		Ok(())
+	}
+}
+
+/// Writes an HTML `<table>`, with the headers in `<thead>` and the rows in `<tbody>`. Fields are HTML-escaped.
+pub struct HtmlTarget<W: io::Write> {
+	writer: W,
+	class: Option<String>,
+}
+impl<W: io::Write> HtmlTarget<W> {
+	pub fn new(writer: W) -> Self {
+		Self {
+			writer,
+			class: None,
+		}
+	}
+	/// Set the CSS class of the `<table>` element
+	pub fn class(mut self, class: &str) -> Self {
+		self.class = Some(class.to_string());
+		self
+	}
+	fn write_row_tag<'a>(
+		&mut self,
+		tag: &str,
+		fields: impl Iterator<Item = &'a str>,
+	) -> io::Result<()> {
+		let mut line = String::from("<tr>");
+		for field in fields {
+			line.push_str(&format!("<{}>{}</{}>", tag, escape_html(field), tag));
+		}
+		line.push_str("</tr>\n");
+		self.writer.write_all(line.as_bytes())
+	}
+}
+impl<W: io::Write> Target for HtmlTarget<W> {
+	fn write_headers(&mut self, headers: &Headers) -> Result<(), csv::Error> {
+		match &self.class {
+			Some(class) => writeln!(self.writer, "<table class=\"{}\">", escape_html(class))?,
+			None => self.writer.write_all(b"<table>\n")?,
+		}
+		self.writer.write_all(b"<thead>\n")?;
+		self.write_row_tag("th", headers.into_iter())?;
+		self.writer.write_all(b"</thead>\n<tbody>\n")?;
+		Ok(())
+	}
+	fn write_row(&mut self, row: &Row) -> Result<(), csv::Error> {
+		self.write_row_tag("td", row.iter())?;
+		Ok(())
+	}
+	fn finish(&mut self) -> Result<(), csv::Error> {
+		self.writer.write_all(b"</tbody>\n</table>\n")?;
+		self.writer.flush()?;
+		Ok(())
+	}
+}
+
+fn escape_html(s: &str) -> String {
+	let mut escaped = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&#39;"),
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+/// Writes an aligned table for reading in a terminal, like many database CLIs do.
+///
+/// Column widths depend on every row, so rows are kept in memory and the table is written when the pipeline finishes.
+pub struct TableTarget<W: io::Write> {
+	writer: W,
+	/// The header and rows
+	rows: Vec<Row>,
+}
+impl<W: io::Write> TableTarget<W> {
+	pub fn new(writer: W) -> Self {
+		Self {
+			writer,
+			rows: Vec::new(),
+		}
+	}
+}
+impl<W: io::Write> Target for TableTarget<W> {
+	fn write_headers(&mut self, headers: &Headers) -> Result<(), csv::Error> {
+		self.rows.push(headers.get_row().clone());
+		Ok(())
+	}
+	fn write_row(&mut self, row: &Row) -> Result<(), csv::Error> {
+		self.rows.push(row.clone());
+		Ok(())
+	}
+	fn finish(&mut self) -> Result<(), csv::Error> {
+		let rows = std::mem::take(&mut self.rows);
+		let widths = column_widths(&rows);
+		let mut border = String::from("+");
+		for width in &widths {
+			border.push_str(&"-".repeat(width + 2));
+			border.push('+');
+		}
+		writeln!(self.writer, "{}", border)?;
+		for (i, row) in rows.iter().enumerate() {
+			let mut line = String::from("|");
+			for (j, width) in widths.iter().enumerate() {
+				let field = row.get(j).unwrap_or_default();
+				line.push_str(&format!(" {:<width$} |", field, width = width));
+			}
+			writeln!(self.writer, "{}", line)?;
+			if i == 0 {
+				writeln!(self.writer, "{}", border)?;
+			}
+		}
+		if rows.len() > 1 {
+			writeln!(self.writer, "{}", border)?;
+		}
+		self.writer.flush()?;
+		Ok(())
+	}
+}
+
+/// The number of characters in the longest field of each column
+fn column_widths(rows: &[Row]) -> Vec<usize> {
+	let mut widths = Vec::new();
+	for row in rows {
+		for (i, field) in row.iter().enumerate() {
+			let len = field.chars().count();
+			match widths.get_mut(i) {
+				Some(width) if *width < len => *width = len,
+				Some(_) => {}
+				None => widths.push(len),
+			}
+		}
+	}
+	widths
+}
+
+#[test]
+fn test_null_token() {
+	use crate::{Pipeline, Target};
+
+	let mut csv = String::new();
+	Pipeline::from_rows(vec![Row::from(vec!["A", "B"]), Row::from(vec!["1", ""])])
+		.unwrap()
+		.flush(Target::string(&mut csv).null_token("\\N"))
+		.run()
+		.unwrap();
+	assert_eq!(csv, "A,B\n1,\\N\n");
+}
+
+#[test]
+fn test_fixed_width() {
+	use crate::{Pipeline, Target};
+
+	let mut output = Vec::new();
+	Pipeline::from_path("test/Countries.csv")
+		.unwrap()
+		.flush(Target::fixed_width(&mut output))
+		.run()
+		.unwrap();
+	assert_eq!(
+		String::from_utf8(output).unwrap(),
+		"IDCountry\n1 Norway \n2 Tuvalu \n"
+	);
+
+	let mut output = Vec::new();
+	Pipeline::from_path("test/Countries.csv")
+		.unwrap()
+		.flush(Target::fixed_width(&mut output).widths(vec![("ID", 4), ("Country", 3)]))
+		.run()
+		.unwrap_err();
+}
+
+#[test]
+fn test_markdown() {
+	use crate::{Pipeline, Target};
+
+	let mut output = Vec::new();
+	Pipeline::from_rows(vec![Row::from(vec!["A", "B"]), Row::from(vec!["x|y", ""])])
+		.unwrap()
+		.flush(Target::markdown(&mut output))
+		.run()
+		.unwrap();
+	assert_eq!(
+		String::from_utf8(output).unwrap(),
+		"| A | B |\n| --- | --- |\n| x\\|y |  |\n"
+	);
+}
+
+#[test]
+fn test_html() {
+	use crate::{Pipeline, Target};
+
+	let mut output = Vec::new();
+	Pipeline::from_rows(vec![Row::from(vec!["A"]), Row::from(vec!["<b>"])])
+		.unwrap()
+		.flush(Target::html(&mut output).class("data"))
+		.run()
+		.unwrap();
+	assert_eq!(
+		String::from_utf8(output).unwrap(),
+		"<table class=\"data\">\n\
+			<thead>\n<tr><th>A</th></tr>\n</thead>\n\
+			<tbody>\n<tr><td>&lt;b&gt;</td></tr>\n</tbody>\n\
+			</table>\n"
+	);
+}
+
+#[test]
+fn test_table() {
+	use crate::{Pipeline, Target};
+
+	let mut output = Vec::new();
+	Pipeline::from_path("test/Countries.csv")
+		.unwrap()
+		.flush(Target::table(&mut output))
+		.run()
+		.unwrap();
+	assert_eq!(
+		String::from_utf8(output).unwrap(),
+		"+----+---------+\n\
+			| ID | Country |\n\
+			+----+---------+\n\
+			| 1  | Norway  |\n\
+			| 2  | Tuvalu  |\n\
+			+----+---------+\n"
+	);
+}
+
+#[test]
+fn test_with_headers() {
+	use crate::{Pipeline, Target};
+
+	let mut csv = String::new();
+	for i in 0..2 {
+		Pipeline::from_path("test/AB.csv")
+			.unwrap()
+			.flush(Target::string(&mut csv).with_headers(i == 0))
+			.run()
+			.unwrap();
+	}
+	assert_eq!(csv, "A,B\n1,2\n1,2\n");
+}
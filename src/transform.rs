@@ -1,42 +1,69 @@
 use crate::{Error, Headers, Row};
 use core::fmt::Display;
-use std::collections::hash_map::DefaultHasher;
+use linked_hash_map::LinkedHashMap;
+use num_traits::CheckedAdd;
+use std::cmp::Ordering;
 use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
 use std::ops::AddAssign;
 use std::str::FromStr;
 
 /// For grouping and reducing rows.
 pub trait Transform {
-	/// Add the row to the hasher to group this row separately from others
-	fn hash(
-		&self,
-		_hasher: &mut DefaultHasher,
-		_headers: &Headers,
-		_row: &Row,
-	) -> Result<(), Error> {
+	/// Add the row to the hasher to group this row separately from others. The hasher is [`DefaultHasher`](std::collections::hash_map::DefaultHasher) unless another one is set using [`TransformOptions::hasher`](crate::TransformOptions::hasher).
+	fn hash(&self, _hasher: &mut dyn Hasher, _headers: &Headers, _row: &Row) -> Result<(), Error> {
+		Ok(())
+	}
+
+	/// Add the fields that group this row separately from others to the key. Rows are only grouped together if both their hashes and keys are equal, so hash collisions don't merge groups.
+	fn key(&self, _key: &mut Vec<String>, _headers: &Headers, _row: &Row) -> Result<(), Error> {
 		Ok(())
 	}
 
 	/// Get the resulting column name
 	fn name(&self) -> String;
 
+	/// Get the columns this transform reads from. This lets you check that the columns exist before running a pipeline.
+	fn source_cols(&self) -> Vec<&str> {
+		Vec::new()
+	}
+
 	/// Combine the row with the value
 	fn add_row(&mut self, headers: &Headers, row: &Row) -> Result<(), Error>;
 
 	/// Turn the current value to a string
 	fn value(&self) -> String;
+
+	/// Get the current state as a string that can be merged into another transform using [`merge`](Transform::merge). Transforms that return `None` can't be used when spilling groups to disk.
+	fn state(&self) -> Option<String> {
+		None
+	}
+
+	/// Merge a state from [`state`](Transform::state) into this transform
+	fn merge(&mut self, _state: &str) -> Result<(), Error> {
+		Err(Error::NotMergeable(self.name()))
+	}
 }
 
 /// A struct for building a [`Transform`], which you can use with [`Pipeline::transform_into`](crate::Pipeline::transform_into).
 pub struct Transformer {
 	pub name: String,
 	pub from_col: String,
+	/// Additional columns for transforms that read more than one column
+	pub from_cols: Vec<String>,
+	/// Number of decimals to format numeric results with
+	pub decimals: Option<usize>,
+	/// Whether sums skip empty fields instead of erroring
+	pub empty_as_zero: bool,
 }
 impl Transformer {
 	pub fn new(col_name: &str) -> Self {
 		Self {
 			name: col_name.to_string(),
 			from_col: col_name.to_string(),
+			from_cols: Vec::new(),
+			decimals: None,
+			empty_as_zero: false,
 		}
 	}
 	/// Specify which column the transform should be based on
@@ -44,6 +71,52 @@ impl Transformer {
 		self.from_col = col_name.to_string();
 		self
 	}
+	/// Specify multiple columns the transform should be based on, for transforms like [`reduce_cols`](Transformer::reduce_cols). The first column also becomes the `from_col`.
+	pub fn from_cols(mut self, col_names: Vec<&str>) -> Self {
+		if let Some(first) = col_names.first() {
+			self.from_col = first.to_string();
+		}
+		self.from_cols = col_names.into_iter().map(String::from).collect();
+		self
+	}
+	/// Format the result with a fixed number of decimals. This applies to [`sum`](Transformer::sum), [`sum_lenient`](Transformer::sum_lenient), [`percentile`](Transformer::percentile) and [`weighted_mean`](Transformer::weighted_mean), for value types that support precision formatting, like `f64`.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Transformer};
+	///
+	/// let csv = Pipeline::from_str("Price\n0.1\n0.2\n")
+	///   .unwrap()
+	///   .transform_into(|| vec![Transformer::new("Price").with_decimals(2).sum(0.0)])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "Price\n0.30\n");
+	/// ```
+	pub fn with_decimals(mut self, decimals: usize) -> Self {
+		self.decimals = Some(decimals);
+		self
+	}
+	/// Treat empty fields as zero instead of returning an [`InvalidField`](Error::InvalidField) error. This applies to [`sum`](Transformer::sum), [`sum_lenient`](Transformer::sum_lenient) and [`sum_checked`](Transformer::sum_checked).
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Transformer};
+	///
+	/// let csv = Pipeline::from_str("Amount\n5\n\"\"\n3\n")
+	///   .unwrap()
+	///   .transform_into(|| vec![Transformer::new("Amount").treat_empty_as_zero().sum(0)])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "Amount\n8\n");
+	/// ```
+	pub fn treat_empty_as_zero(mut self) -> Self {
+		self.empty_as_zero = true;
+		self
+	}
 	/// Keep the unique values from this column
 	pub fn keep_unique(self) -> Box<dyn Transform> {
 		Box::new(KeepUnique {
@@ -58,9 +131,83 @@ impl Transformer {
 		N: Display + AddAssign + FromStr + Clone + 'a,
 	{
 		Box::new(Sum {
+			name: self.name,
+			from_col: self.from_col,
+			init,
+			value: None,
+			lenient: false,
+			decimals: self.decimals,
+			empty_as_zero: self.empty_as_zero,
+		})
+	}
+	/// Sum the values in this column, silently skipping fields that can't be parsed, like `N/A`. Use [`count_invalid`](Transformer::count_invalid) to find out how many fields were skipped.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Transformer};
+	///
+	/// let csv = Pipeline::from_str("Score\n1\nN/A\n2\n")
+	///   .unwrap()
+	///   .transform_into(|| {
+	///     vec![
+	///       Transformer::new("Score").sum_lenient(0),
+	///       Transformer::new("Skipped").from_col("Score").count_invalid::<i32>(),
+	///     ]
+	///   })
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "Score,Skipped\n3,1\n");
+	/// ```
+	pub fn sum_lenient<'a, N>(self, init: N) -> Box<dyn Transform + 'a>
+	where
+		N: Display + AddAssign + FromStr + Clone + 'a,
+	{
+		Box::new(Sum {
+			name: self.name,
+			from_col: self.from_col,
+			init,
+			value: None,
+			lenient: true,
+			decimals: self.decimals,
+			empty_as_zero: self.empty_as_zero,
+		})
+	}
+	/// Sum the values in this column, returning an [`Overflow`](Error::Overflow) error instead of wrapping around if the sum doesn't fit in `N`.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Error, Pipeline, Transformer};
+	///
+	/// let err = Pipeline::from_str("Bytes\n200\n100\n")
+	///   .unwrap()
+	///   .transform_into(|| vec![Transformer::new("Bytes").sum_checked(0u8)])
+	///   .collect_into_string()
+	///   .unwrap_err();
+	///
+	/// assert!(matches!(err.error, Error::Overflow(col) if col == "Bytes"));
+	/// ```
+	pub fn sum_checked<'a, N>(self, init: N) -> Box<dyn Transform + 'a>
+	where
+		N: Display + CheckedAdd + FromStr + 'a,
+	{
+		Box::new(CheckedSum {
 			name: self.name,
 			from_col: self.from_col,
 			value: init,
+			added: None,
+			empty_as_zero: self.empty_as_zero,
+		})
+	}
+	/// Count the fields in this column that can't be parsed as `N`.
+	pub fn count_invalid<'a, N: FromStr + 'a>(self) -> Box<dyn Transform + 'a> {
+		Box::new(CountInvalid::<N> {
+			name: self.name,
+			from_col: self.from_col,
+			value: 0,
+			parse_as: PhantomData,
 		})
 	}
 	/// Reduce the values from this column into a single value using a closure.
@@ -77,6 +224,211 @@ impl Transformer {
 		})
 	}
 
+	/// Compute the `p`th percentile of the values in this column, where `p` is in `[0, 100]`.
+	///
+	/// Values are parsed as `f64`, and the result is linearly interpolated between the two closest ranks. Every value of the group is kept in memory until the group is finished. An empty group results in an empty field. Panics if `p` is outside `[0, 100]`.
+	pub fn percentile(self, p: f64) -> Box<dyn Transform> {
+		assert!(
+			(0.0..=100.0).contains(&p),
+			"Percentile must be between 0 and 100, got {}",
+			p
+		);
+		Box::new(Percentile {
+			name: self.name,
+			from_col: self.from_col,
+			p,
+			values: Vec::new(),
+			decimals: self.decimals,
+		})
+	}
+
+	/// Get the most frequent value in this column. Ties are broken by which value was seen first. An empty group results in an empty field.
+	pub fn mode(self) -> Box<dyn Transform> {
+		Box::new(Mode {
+			name: self.name,
+			from_col: self.from_col,
+			counts: LinkedHashMap::new(),
+		})
+	}
+
+	/// Compute the mean of `value_col` weighted by `weight_col`, as `sum(value * weight) / sum(weight)`. Both columns are parsed as `f64`, and this transform's `from_col` is ignored. If the weights sum to zero, the result is an empty field.
+	pub fn weighted_mean(self, value_col: &str, weight_col: &str) -> Box<dyn Transform> {
+		Box::new(WeightedMean {
+			name: self.name,
+			value_col: value_col.to_string(),
+			weight_col: weight_col.to_string(),
+			weighted_sum: 0.0,
+			total_weight: 0.0,
+			decimals: self.decimals,
+		})
+	}
+
+	/// Reduce the values from multiple columns into a single value using a closure. The closure receives the fields of the [`from_cols`](Transformer::from_cols) columns in the same order, or just the `from_col` field if `from_cols` wasn't used.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Transformer};
+	///
+	/// let source = "\
+	///   Item,Price,Qty\n\
+	///   a,10,1\n\
+	///   a,20,3\n";
+	/// let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+	///   .unwrap()
+	///   .transform_into(|| {
+	///     vec![
+	///       Transformer::new("Item").keep_unique(),
+	///       Transformer::new("Revenue").from_cols(vec!["Price", "Qty"]).reduce_cols(
+	///         |total, fields| {
+	///           let price: u32 = fields[0].parse().unwrap();
+	///           let qty: u32 = fields[1].parse().unwrap();
+	///           Ok(total + price * qty)
+	///         },
+	///         0,
+	///       ),
+	///     ]
+	///   })
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "Item,Revenue\na,70\n");
+	/// ```
+	pub fn reduce_cols<'a, R, V>(self, reduce: R, init: V) -> Box<dyn Transform + 'a>
+	where
+		R: FnMut(V, &[&str]) -> Result<V, Error> + 'a,
+		V: Display + Clone + 'a,
+	{
+		let from_cols = match self.from_cols.is_empty() {
+			true => vec![self.from_col],
+			false => self.from_cols,
+		};
+		Box::new(ReduceCols {
+			name: self.name,
+			from_cols,
+			reduce,
+			value: init,
+		})
+	}
+
+	/// Get the lowest value in this column, comparing fields as text. An empty group results in an empty field.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Transformer};
+	///
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .transform_into(|| {
+	///     vec![
+	///       Transformer::new("First").from_col("Country").min_text(),
+	///       Transformer::new("Last").from_col("Country").max_text(),
+	///     ]
+	///   })
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "First,Last\nNorway,Tuvalu\n");
+	/// ```
+	pub fn min_text(self) -> Box<dyn Transform> {
+		Box::new(TextExtreme {
+			name: self.name,
+			from_col: self.from_col,
+			keep: Ordering::Less,
+			value: None,
+		})
+	}
+
+	/// Get the highest value in this column, comparing fields as text. An empty group results in an empty field.
+	pub fn max_text(self) -> Box<dyn Transform> {
+		Box::new(TextExtreme {
+			name: self.name,
+			from_col: self.from_col,
+			keep: Ordering::Greater,
+			value: None,
+		})
+	}
+
+	/// Get the earliest date in this column. Fields are parsed using a [`chrono` format string](chrono::format::strftime), either as a date and time or as just a date, and the original field is kept in the output. Fields that can't be parsed result in an [`InvalidField`](Error::InvalidField) error.
+	///
+	/// Requires the `chrono` feature.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Transformer};
+	///
+	/// let source = "\
+	///   User,Date\n\
+	///   a,03/02/2024\n\
+	///   a,15/01/2024\n\
+	///   a,01/03/2024\n";
+	/// let csv = Pipeline::from_str(source)
+	///   .unwrap()
+	///   .transform_into(|| {
+	///     vec![
+	///       Transformer::new("User").keep_unique(),
+	///       Transformer::new("First").from_col("Date").min_date("%d/%m/%Y"),
+	///       Transformer::new("Last").from_col("Date").max_date("%d/%m/%Y"),
+	///     ]
+	///   })
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "User,First,Last\na,15/01/2024,01/03/2024\n");
+	/// ```
+	#[cfg(feature = "chrono")]
+	pub fn min_date(self, format: &str) -> Box<dyn Transform> {
+		Box::new(DateExtreme {
+			name: self.name,
+			from_col: self.from_col,
+			format: format.to_string(),
+			keep: Ordering::Less,
+			value: None,
+		})
+	}
+
+	/// Get the latest date in this column. Works like [`min_date`](Transformer::min_date).
+	///
+	/// Requires the `chrono` feature.
+	#[cfg(feature = "chrono")]
+	pub fn max_date(self, format: &str) -> Box<dyn Transform> {
+		Box::new(DateExtreme {
+			name: self.name,
+			from_col: self.from_col,
+			format: format.to_string(),
+			keep: Ordering::Greater,
+			value: None,
+		})
+	}
+
+	/// Collect the values from this column into a JSON array of strings, like `["a","b"]`.
+	///
+	/// Requires the `serde_json` feature.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use csv_pipeline::{Pipeline, Transformer};
+	///
+	/// let csv = Pipeline::from_path("test/Countries.csv")
+	///   .unwrap()
+	///   .transform_into(|| vec![Transformer::new("Countries").from_col("Country").collect_json()])
+	///   .collect_into_string()
+	///   .unwrap();
+	///
+	/// assert_eq!(csv, "Countries\n\"[\"\"Norway\"\",\"\"Tuvalu\"\"]\"\n");
+	/// ```
+	#[cfg(feature = "serde_json")]
+	pub fn collect_json(self) -> Box<dyn Transform> {
+		Box::new(CollectJson {
+			name: self.name,
+			from_col: self.from_col,
+			values: Vec::new(),
+		})
+	}
+
 	/// Count the rows that were reduced into this row.
 	pub fn count(self) -> Box<dyn Transform> {
 		Box::new(Count {
@@ -92,17 +444,28 @@ struct KeepUnique {
 	value: String,
 }
 impl Transform for KeepUnique {
-	fn hash(&self, hasher: &mut DefaultHasher, headers: &Headers, row: &Row) -> Result<(), Error> {
+	fn hash(&self, mut hasher: &mut dyn Hasher, headers: &Headers, row: &Row) -> Result<(), Error> {
 		let field = headers
 			.get_field(row, &self.from_col)
 			.ok_or(Error::MissingColumn(self.from_col.clone()))?;
-		field.hash(hasher);
+		field.hash(&mut hasher);
+		Ok(())
+	}
+
+	fn key(&self, key: &mut Vec<String>, headers: &Headers, row: &Row) -> Result<(), Error> {
+		let field = headers
+			.get_field(row, &self.from_col)
+			.ok_or(Error::MissingColumn(self.from_col.clone()))?;
+		key.push(field.to_string());
 		Ok(())
 	}
 
 	fn name(&self) -> String {
 		self.name.clone()
 	}
+	fn source_cols(&self) -> Vec<&str> {
+		vec![&self.from_col]
+	}
 
 	fn add_row(&mut self, headers: &Headers, row: &Row) -> Result<(), Error> {
 		self.value = headers
@@ -115,21 +478,39 @@ impl Transform for KeepUnique {
 	fn value(&self) -> String {
 		self.value.clone()
 	}
+
+	fn state(&self) -> Option<String> {
+		Some(self.value.clone())
+	}
+
+	fn merge(&mut self, state: &str) -> Result<(), Error> {
+		self.value = state.to_string();
+		Ok(())
+	}
 }
 
-pub(crate) fn compute_hash<'a>(
+/// The group of a row, as its hash and its key fields
+pub(crate) type GroupKey = (u64, Vec<String>);
+
+pub(crate) fn compute_key<'a>(
 	transformers: &Vec<Box<dyn Transform + 'a>>,
+	hasher: &mut dyn Hasher,
 	headers: &Headers,
 	row: &Row,
-) -> Result<u64, Error> {
-	let mut hasher = DefaultHasher::new();
+) -> Result<GroupKey, Error> {
+	let mut key = Vec::new();
 	for transformer in transformers {
-		let result = transformer.hash(&mut hasher, &headers, &row);
-		if let Err(e) = result {
-			return Err(e);
-		}
+		transformer.hash(hasher, headers, row)?;
+		transformer.key(&mut key, headers, row)?;
+	}
+	Ok((hasher.finish(), key))
+}
+
+fn format_decimals(value: impl Display, decimals: Option<usize>) -> String {
+	match decimals {
+		Some(decimals) => format!("{:.*}", decimals, value),
+		None => value.to_string(),
 	}
-	Ok(hasher.finish())
 }
 
 struct Reduce<F, V> {
@@ -159,12 +540,68 @@ where
 	fn name(&self) -> String {
 		self.name.clone()
 	}
+	fn source_cols(&self) -> Vec<&str> {
+		vec![&self.from_col]
+	}
+}
+
+struct ReduceCols<F, V> {
+	name: String,
+	from_cols: Vec<String>,
+	reduce: F,
+	value: V,
+}
+impl<F, V> Transform for ReduceCols<F, V>
+where
+	F: FnMut(V, &[&str]) -> Result<V, Error>,
+	V: Display + Clone,
+{
+	fn add_row(&mut self, headers: &Headers, row: &Row) -> Result<(), Error> {
+		let mut fields = Vec::with_capacity(self.from_cols.len());
+		for col in &self.from_cols {
+			let field = headers
+				.get_field(row, col)
+				.ok_or(Error::MissingColumn(col.clone()))?;
+			fields.push(field);
+		}
+		self.value = (self.reduce)(self.value.clone(), &fields)?;
+		Ok(())
+	}
+
+	fn value(&self) -> String {
+		self.value.to_string()
+	}
+
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+	fn source_cols(&self) -> Vec<&str> {
+		self.from_cols.iter().map(String::as_str).collect()
+	}
 }
 
 struct Sum<N> {
 	name: String,
 	from_col: String,
-	value: N,
+	init: N,
+	/// The sum of the added fields. This is kept apart from `init` so that merging spilled states doesn't count `init` more than once.
+	value: Option<N>,
+	/// Skip fields that can't be parsed instead of erroring
+	lenient: bool,
+	decimals: Option<usize>,
+	/// Skip empty fields
+	empty_as_zero: bool,
+}
+impl<V> Sum<V>
+where
+	V: AddAssign,
+{
+	fn add(&mut self, new: V) {
+		match &mut self.value {
+			Some(value) => *value += new,
+			None => self.value = Some(new),
+		}
+	}
 }
 impl<V> Transform for Sum<V>
 where
@@ -175,22 +612,46 @@ where
 			.get_field(row, &self.from_col)
 			.ok_or(Error::MissingColumn(self.from_col.clone()))?
 			.to_string();
+		if self.empty_as_zero && field.is_empty() {
+			return Ok(());
+		}
 		let new: V = match field.parse() {
 			Ok(v) => v,
+			Err(_) if self.lenient => return Ok(()),
 			Err(_) => return Err(Error::InvalidField(field)),
 		};
-		println!("+ {}", new);
-		self.value += new;
-		println!("= {}", self.value);
+		self.add(new);
 		Ok(())
 	}
 
 	fn value(&self) -> String {
-		self.value.to_string()
+		let mut value = self.init.clone();
+		if let Some(added) = &self.value {
+			value += added.clone();
+		}
+		format_decimals(&value, self.decimals)
 	}
 	fn name(&self) -> String {
 		self.name.clone()
 	}
+	fn source_cols(&self) -> Vec<&str> {
+		vec![&self.from_col]
+	}
+
+	fn state(&self) -> Option<String> {
+		Some(self.value.as_ref().map(V::to_string).unwrap_or_default())
+	}
+
+	fn merge(&mut self, state: &str) -> Result<(), Error> {
+		if state.is_empty() {
+			return Ok(());
+		}
+		match state.parse() {
+			Ok(v) => self.add(v),
+			Err(_) => return Err(Error::InvalidField(state.to_string())),
+		};
+		Ok(())
+	}
 }
 #[test]
 fn test_sum() {
@@ -234,6 +695,256 @@ fn test_sum() {
 	);
 }
 
+struct CheckedSum<N> {
+	name: String,
+	from_col: String,
+	value: N,
+	/// The sum without `init`, which is what gets merged when spilling to disk
+	added: Option<N>,
+	/// Skip empty fields
+	empty_as_zero: bool,
+}
+impl<N> CheckedSum<N>
+where
+	N: CheckedAdd,
+{
+	fn add(&mut self, new: N) -> Result<(), Error> {
+		let overflow = || Error::Overflow(self.from_col.clone());
+		let value = self.value.checked_add(&new).ok_or_else(overflow)?;
+		let added = match &self.added {
+			Some(added) => added.checked_add(&new).ok_or_else(overflow)?,
+			None => new,
+		};
+		self.value = value;
+		self.added = Some(added);
+		Ok(())
+	}
+}
+impl<N> Transform for CheckedSum<N>
+where
+	N: Display + CheckedAdd + FromStr,
+{
+	fn add_row(&mut self, headers: &Headers, row: &Row) -> Result<(), Error> {
+		let field = headers
+			.get_field(row, &self.from_col)
+			.ok_or(Error::MissingColumn(self.from_col.clone()))?;
+		if self.empty_as_zero && field.is_empty() {
+			return Ok(());
+		}
+		match field.parse() {
+			Ok(new) => self.add(new),
+			Err(_) => Err(Error::InvalidField(field.to_string())),
+		}
+	}
+
+	fn value(&self) -> String {
+		self.value.to_string()
+	}
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+	fn source_cols(&self) -> Vec<&str> {
+		vec![&self.from_col]
+	}
+
+	fn state(&self) -> Option<String> {
+		Some(self.added.as_ref().map(N::to_string).unwrap_or_default())
+	}
+
+	fn merge(&mut self, state: &str) -> Result<(), Error> {
+		if state.is_empty() {
+			return Ok(());
+		}
+		match state.parse() {
+			Ok(v) => self.add(v),
+			Err(_) => Err(Error::InvalidField(state.to_string())),
+		}
+	}
+}
+
+struct CountInvalid<N> {
+	name: String,
+	from_col: String,
+	value: u128,
+	parse_as: PhantomData<N>,
+}
+impl<N: FromStr> Transform for CountInvalid<N> {
+	fn add_row(&mut self, headers: &Headers, row: &Row) -> Result<(), Error> {
+		let field = headers
+			.get_field(row, &self.from_col)
+			.ok_or(Error::MissingColumn(self.from_col.clone()))?;
+		if field.parse::<N>().is_err() {
+			self.value += 1;
+		}
+		Ok(())
+	}
+
+	fn value(&self) -> String {
+		self.value.to_string()
+	}
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+	fn source_cols(&self) -> Vec<&str> {
+		vec![&self.from_col]
+	}
+
+	fn state(&self) -> Option<String> {
+		Some(self.value.to_string())
+	}
+
+	fn merge(&mut self, state: &str) -> Result<(), Error> {
+		match state.parse::<u128>() {
+			Ok(v) => self.value += v,
+			Err(_) => return Err(Error::InvalidField(state.to_string())),
+		};
+		Ok(())
+	}
+}
+
+/// Keeps the lowest or highest field, as text
+struct TextExtreme {
+	name: String,
+	from_col: String,
+	/// `Less` to keep the lowest value, `Greater` to keep the highest
+	keep: Ordering,
+	value: Option<String>,
+}
+impl TextExtreme {
+	fn add(&mut self, field: &str) {
+		match &self.value {
+			Some(value) if field.cmp(value) != self.keep => {}
+			_ => self.value = Some(field.to_string()),
+		}
+	}
+}
+impl Transform for TextExtreme {
+	fn add_row(&mut self, headers: &Headers, row: &Row) -> Result<(), Error> {
+		let field = headers
+			.get_field(row, &self.from_col)
+			.ok_or(Error::MissingColumn(self.from_col.clone()))?;
+		self.add(field);
+		Ok(())
+	}
+
+	fn value(&self) -> String {
+		self.value.clone().unwrap_or_default()
+	}
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+	fn source_cols(&self) -> Vec<&str> {
+		vec![&self.from_col]
+	}
+
+	fn state(&self) -> Option<String> {
+		Some(self.value())
+	}
+
+	fn merge(&mut self, state: &str) -> Result<(), Error> {
+		self.add(state);
+		Ok(())
+	}
+}
+
+/// Keeps the earliest or latest date
+#[cfg(feature = "chrono")]
+struct DateExtreme {
+	name: String,
+	from_col: String,
+	format: String,
+	/// `Less` to keep the earliest date, `Greater` to keep the latest
+	keep: Ordering,
+	/// The parsed date and the original field
+	value: Option<(chrono::NaiveDateTime, String)>,
+}
+#[cfg(feature = "chrono")]
+impl DateExtreme {
+	fn add(&mut self, field: &str) -> Result<(), Error> {
+		use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+		let date = NaiveDateTime::parse_from_str(field, &self.format)
+			.or_else(|_| {
+				NaiveDate::parse_from_str(field, &self.format)
+					.map(|date| date.and_time(NaiveTime::default()))
+			})
+			.map_err(|_| Error::InvalidField(field.to_string()))?;
+		match &self.value {
+			Some((value, _)) if date.cmp(value) != self.keep => {}
+			_ => self.value = Some((date, field.to_string())),
+		}
+		Ok(())
+	}
+}
+#[cfg(feature = "chrono")]
+impl Transform for DateExtreme {
+	fn add_row(&mut self, headers: &Headers, row: &Row) -> Result<(), Error> {
+		let field = headers
+			.get_field(row, &self.from_col)
+			.ok_or(Error::MissingColumn(self.from_col.clone()))?;
+		self.add(field)
+	}
+
+	fn value(&self) -> String {
+		match &self.value {
+			Some((_, field)) => field.clone(),
+			None => String::new(),
+		}
+	}
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+	fn source_cols(&self) -> Vec<&str> {
+		vec![&self.from_col]
+	}
+
+	fn state(&self) -> Option<String> {
+		Some(self.value())
+	}
+
+	fn merge(&mut self, state: &str) -> Result<(), Error> {
+		self.add(state)
+	}
+}
+
+#[cfg(feature = "serde_json")]
+struct CollectJson {
+	name: String,
+	from_col: String,
+	values: Vec<String>,
+}
+#[cfg(feature = "serde_json")]
+impl Transform for CollectJson {
+	fn add_row(&mut self, headers: &Headers, row: &Row) -> Result<(), Error> {
+		let field = headers
+			.get_field(row, &self.from_col)
+			.ok_or(Error::MissingColumn(self.from_col.clone()))?;
+		self.values.push(field.to_string());
+		Ok(())
+	}
+
+	fn value(&self) -> String {
+		serde_json::to_string(&self.values).unwrap()
+	}
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+	fn source_cols(&self) -> Vec<&str> {
+		vec![&self.from_col]
+	}
+
+	fn state(&self) -> Option<String> {
+		Some(self.value())
+	}
+
+	fn merge(&mut self, state: &str) -> Result<(), Error> {
+		let values: Vec<String> =
+			serde_json::from_str(state).map_err(|_| Error::InvalidField(state.to_string()))?;
+		self.values.extend(values);
+		Ok(())
+	}
+}
+
 struct Count {
 	name: String,
 	value: u128,
@@ -250,4 +961,256 @@ impl Transform for Count {
 	fn name(&self) -> String {
 		self.name.clone()
 	}
+
+	fn state(&self) -> Option<String> {
+		Some(self.value.to_string())
+	}
+
+	fn merge(&mut self, state: &str) -> Result<(), Error> {
+		match state.parse::<u128>() {
+			Ok(v) => self.value += v,
+			Err(_) => return Err(Error::InvalidField(state.to_string())),
+		};
+		Ok(())
+	}
+}
+
+struct Percentile {
+	name: String,
+	from_col: String,
+	p: f64,
+	values: Vec<f64>,
+	decimals: Option<usize>,
+}
+impl Transform for Percentile {
+	fn add_row(&mut self, headers: &Headers, row: &Row) -> Result<(), Error> {
+		let field = headers
+			.get_field(row, &self.from_col)
+			.ok_or(Error::MissingColumn(self.from_col.clone()))?;
+		match field.parse() {
+			Ok(v) => self.values.push(v),
+			Err(_) => return Err(Error::InvalidField(field.to_string())),
+		};
+		Ok(())
+	}
+
+	fn value(&self) -> String {
+		if self.values.is_empty() {
+			return String::new();
+		}
+		let mut values = self.values.clone();
+		values.sort_by(f64::total_cmp);
+		let rank = self.p / 100.0 * (values.len() - 1) as f64;
+		let lower = values[rank.floor() as usize];
+		let upper = values[rank.ceil() as usize];
+		format_decimals(lower + (upper - lower) * rank.fract(), self.decimals)
+	}
+
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+	fn source_cols(&self) -> Vec<&str> {
+		vec![&self.from_col]
+	}
+}
+#[test]
+fn test_percentile() {
+	use crate::{Pipeline, Transformer};
+
+	let source = "\
+		Endpoint,Latency\n\
+		a,10\n\
+		a,40\n\
+		a,20\n\
+		a,30\n\
+		b,5\n";
+	let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+		.unwrap()
+		.transform_into(|| {
+			vec![
+				Transformer::new("Endpoint").keep_unique(),
+				Transformer::new("p50").from_col("Latency").percentile(50.0),
+				Transformer::new("p90").from_col("Latency").percentile(90.0),
+			]
+		})
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "Endpoint,p50,p90\na,25,37\nb,5,5\n");
+}
+
+struct Mode {
+	name: String,
+	from_col: String,
+	/// Value frequencies in first-seen order
+	counts: LinkedHashMap<String, usize>,
+}
+impl Transform for Mode {
+	fn add_row(&mut self, headers: &Headers, row: &Row) -> Result<(), Error> {
+		let field = headers
+			.get_field(row, &self.from_col)
+			.ok_or(Error::MissingColumn(self.from_col.clone()))?;
+		*self.counts.entry(field.to_string()).or_insert(0) += 1;
+		Ok(())
+	}
+
+	fn value(&self) -> String {
+		let mut mode: Option<(&String, usize)> = None;
+		for (value, &count) in &self.counts {
+			match mode {
+				Some((_, max)) if max >= count => {}
+				_ => mode = Some((value, count)),
+			}
+		}
+		mode.map(|(value, _)| value.clone()).unwrap_or_default()
+	}
+
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+	fn source_cols(&self) -> Vec<&str> {
+		vec![&self.from_col]
+	}
+}
+#[test]
+fn test_mode() {
+	use crate::{Pipeline, Transformer};
+
+	let source = "\
+		Group,Value\n\
+		a,x\n\
+		a,y\n\
+		a,y\n\
+		b,y\n\
+		b,x\n\
+		c,z\n\
+		c,y\n";
+	let csv = Pipeline::from_str(source)
+		.unwrap()
+		.transform_into(|| {
+			vec![
+				Transformer::new("Group").keep_unique(),
+				Transformer::new("Mode").from_col("Value").mode(),
+			]
+		})
+		.collect_into_string()
+		.unwrap();
+	// Ties go to the value that was seen first
+	assert_eq!(csv, "Group,Mode\na,y\nb,y\nc,z\n");
+
+	let csv = Pipeline::from_str("Value\n")
+		.unwrap()
+		.aggregate(vec![
+			Transformer::new("Mode").from_col("Value").mode(),
+			Transformer::new("Count").count(),
+		])
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "Mode,Count\n,0\n");
+}
+
+struct WeightedMean {
+	name: String,
+	value_col: String,
+	weight_col: String,
+	weighted_sum: f64,
+	total_weight: f64,
+	decimals: Option<usize>,
+}
+impl Transform for WeightedMean {
+	fn add_row(&mut self, headers: &Headers, row: &Row) -> Result<(), Error> {
+		let parse = |col: &String| -> Result<f64, Error> {
+			let field = headers
+				.get_field(row, col)
+				.ok_or(Error::MissingColumn(col.clone()))?;
+			field
+				.parse()
+				.map_err(|_| Error::InvalidField(field.to_string()))
+		};
+		let value = parse(&self.value_col)?;
+		let weight = parse(&self.weight_col)?;
+		self.weighted_sum += value * weight;
+		self.total_weight += weight;
+		Ok(())
+	}
+
+	fn value(&self) -> String {
+		if self.total_weight == 0.0 {
+			return String::new();
+		}
+		format_decimals(self.weighted_sum / self.total_weight, self.decimals)
+	}
+
+	fn name(&self) -> String {
+		self.name.clone()
+	}
+	fn source_cols(&self) -> Vec<&str> {
+		vec![&self.value_col, &self.weight_col]
+	}
+
+	fn state(&self) -> Option<String> {
+		Some(format!("{} {}", self.weighted_sum, self.total_weight))
+	}
+
+	fn merge(&mut self, state: &str) -> Result<(), Error> {
+		let invalid = || Error::InvalidField(state.to_string());
+		let (weighted_sum, total_weight) = state.split_once(' ').ok_or_else(invalid)?;
+		self.weighted_sum += weighted_sum.parse::<f64>().map_err(|_| invalid())?;
+		self.total_weight += total_weight.parse::<f64>().map_err(|_| invalid())?;
+		Ok(())
+	}
+}
+#[test]
+fn test_weighted_mean() {
+	use crate::{Pipeline, Transformer};
+
+	let source = "\
+		Item,Price,Qty\n\
+		a,10,1\n\
+		a,20,3\n\
+		b,5,0\n";
+	let csv = Pipeline::from_reader(csv::Reader::from_reader(source.as_bytes()))
+		.unwrap()
+		.transform_into(|| {
+			vec![
+				Transformer::new("Item").keep_unique(),
+				Transformer::new("Price").weighted_mean("Price", "Qty"),
+			]
+		})
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "Item,Price\na,17.5\nb,\n");
+}
+
+#[test]
+fn test_groups_compare_keys() {
+	use crate::Pipeline;
+
+	// Never adds to the hash, so every row has the same hash
+	struct KeyOnly(Box<dyn Transform>);
+	impl Transform for KeyOnly {
+		fn key(&self, key: &mut Vec<String>, headers: &Headers, row: &Row) -> Result<(), Error> {
+			self.0.key(key, headers, row)
+		}
+		fn name(&self) -> String {
+			self.0.name()
+		}
+		fn add_row(&mut self, headers: &Headers, row: &Row) -> Result<(), Error> {
+			self.0.add_row(headers, row)
+		}
+		fn value(&self) -> String {
+			self.0.value()
+		}
+	}
+
+	let csv = Pipeline::from_path("test/Countries.csv")
+		.unwrap()
+		.transform_into(|| {
+			vec![
+				Box::new(KeyOnly(Transformer::new("Country").keep_unique())),
+				Transformer::new("Count").count(),
+			]
+		})
+		.collect_into_string()
+		.unwrap();
+	assert_eq!(csv, "Country,Count\nNorway,1\nTuvalu,1\n");
 }